@@ -0,0 +1,60 @@
+//! Capture sources and their mute flags. Transcript chunks are not attributed
+//! to a source: ScreenCaptureKit mixes the mic into the system audio stream
+//! and the other platforms capture the mic alone, so speakers are told apart
+//! by speaker id only.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum AudioSource {
+    Mic,
+    SystemAudio,
+}
+
+impl AudioSource {
+    pub const ALL: [AudioSource; 2] = [AudioSource::Mic, AudioSource::SystemAudio];
+
+    /// Serialized name, for error context
+    pub fn key(self) -> &'static str {
+        match self {
+            AudioSource::Mic => "mic",
+            AudioSource::SystemAudio => "systemAudio",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AudioSource::Mic => "Mic",
+            AudioSource::SystemAudio => "System audio",
+        }
+    }
+}
+
+/// Set by `set_source_muted`, read by the audio callbacks
+#[derive(Default)]
+pub struct SourceMutes {
+    mic: AtomicBool,
+    system_audio: AtomicBool,
+}
+
+impl SourceMutes {
+    fn flag(&self, source: AudioSource) -> &AtomicBool {
+        match source {
+            AudioSource::Mic => &self.mic,
+            AudioSource::SystemAudio => &self.system_audio,
+        }
+    }
+
+    pub fn is_muted(&self, source: AudioSource) -> bool {
+        self.flag(source).load(Ordering::Acquire)
+    }
+
+    /// Returns whether the flag changed
+    pub fn set(&self, source: AudioSource, muted: bool) -> bool {
+        self.flag(source).swap(muted, Ordering::AcqRel) != muted
+    }
+}
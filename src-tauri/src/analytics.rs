@@ -0,0 +1,232 @@
+//! Conversation metrics derived from the transcript: talk time, pace,
+//! monologues, interruptions, silences and answer latency per speaker.
+//! Recomputed from the chunks on every snapshot so speaker map changes apply.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::speakers::SpeakerRole;
+use crate::transcript::{ChunkKind, TranscriptChunk};
+
+/// Gaps between chunks at least this long count as silence and end a monologue
+const SILENCE_GAP_SECS: f64 = 3.0;
+
+/// Overlap of a speaker change at which it counts as an interruption, not a backchannel
+const INTERRUPTION_OVERLAP_SECS: f64 = 1.0;
+
+/// `conversation-analytics`, also returned by `get_conversation_analytics`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConversationAnalytics {
+    /// Session offset the metrics run up to
+    pub elapsed_secs: f64,
+    pub speech_secs: f64,
+    /// Ordered by talk time, most first
+    pub speakers: Vec<SpeakerAnalytics>,
+    /// Speaker changes where the next speaker started before the previous finished
+    pub overlaps: u32,
+    /// Gaps of at least 3 s without speech, pauses excluded
+    pub silence_gaps: u32,
+    pub silence_secs: f64,
+    pub longest_silence_secs: f64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SpeakerAnalytics {
+    /// Display name, as on the transcript chunks
+    pub speaker: String,
+    pub role: Option<SpeakerRole>,
+    pub talk_secs: f64,
+    /// 0–1 of all speech
+    pub talk_share: f64,
+    pub words: u32,
+    pub words_per_minute: f64,
+    pub turns: u32,
+    pub longest_monologue_secs: f64,
+    /// Times this speaker cut in on someone else
+    pub interruptions: u32,
+    /// Times someone else cut in on this speaker
+    pub interrupted: u32,
+    /// Mean gap between the previous speaker finishing and this one starting
+    pub mean_answer_latency_secs: Option<f64>,
+    pub last_answer_latency_secs: Option<f64>,
+}
+
+impl SpeakerAnalytics {
+    fn new(speaker: &str) -> Self {
+        Self {
+            speaker: speaker.to_string(),
+            role: None,
+            talk_secs: 0.0,
+            talk_share: 0.0,
+            words: 0,
+            words_per_minute: 0.0,
+            turns: 0,
+            longest_monologue_secs: 0.0,
+            interruptions: 0,
+            interrupted: 0,
+            mean_answer_latency_secs: None,
+            last_answer_latency_secs: None,
+        }
+    }
+}
+
+/// Metrics over `chunks` (in seq order) up to session offset `elapsed_secs`
+pub fn analyze(chunks: &[TranscriptChunk], elapsed_secs: f64) -> ConversationAnalytics {
+    let mut speakers: Vec<SpeakerAnalytics> = Vec::new();
+    let mut latencies: Vec<(f64, u32)> = Vec::new();
+    let mut analytics = ConversationAnalytics {
+        elapsed_secs,
+        speech_secs: 0.0,
+        speakers: Vec::new(),
+        overlaps: 0,
+        silence_gaps: 0,
+        silence_secs: 0.0,
+        longest_silence_secs: 0.0,
+    };
+    // Previous speech chunk (speaker index, end) and start of the current monologue
+    let mut previous: Option<(usize, f64)> = None;
+    let mut monologue_start = 0.0;
+
+    for chunk in chunks {
+        match chunk.kind {
+            ChunkKind::Speech => {}
+            // Time paused is neither silence nor an answer
+            ChunkKind::Pause => {
+                previous = None;
+                continue;
+            }
+            ChunkKind::Mute => continue,
+        }
+        let index = match speakers.iter().position(|s| s.speaker == chunk.speaker) {
+            Some(i) => i,
+            None => {
+                speakers.push(SpeakerAnalytics::new(&chunk.speaker));
+                latencies.push((0.0, 0));
+                speakers.len() - 1
+            }
+        };
+        let talk = (chunk.end - chunk.start).max(0.0);
+        let speaker = &mut speakers[index];
+        speaker.role = chunk.role.or(speaker.role);
+        speaker.talk_secs += talk;
+        speaker.words += chunk.text.split_whitespace().count() as u32;
+        analytics.speech_secs += talk;
+
+        let gap = previous.map(|(_, end)| chunk.start - end);
+        if let Some(gap) = gap.filter(|&g| g >= SILENCE_GAP_SECS) {
+            analytics.silence_gaps += 1;
+            analytics.silence_secs += gap;
+            analytics.longest_silence_secs = analytics.longest_silence_secs.max(gap);
+        }
+        match previous {
+            Some((prev, end)) if prev == index && chunk.start - end < SILENCE_GAP_SECS => {}
+            Some((prev, end)) => {
+                speakers[index].turns += 1;
+                monologue_start = chunk.start;
+                if prev != index {
+                    let gap = chunk.start - end;
+                    if gap < 0.0 {
+                        analytics.overlaps += 1;
+                        if -gap >= INTERRUPTION_OVERLAP_SECS {
+                            speakers[index].interruptions += 1;
+                            speakers[prev].interrupted += 1;
+                        }
+                    } else {
+                        let (sum, count) = &mut latencies[index];
+                        *sum += gap;
+                        *count += 1;
+                        speakers[index].last_answer_latency_secs = Some(gap);
+                    }
+                }
+            }
+            None => {
+                speakers[index].turns += 1;
+                monologue_start = chunk.start;
+            }
+        }
+        let speaker = &mut speakers[index];
+        speaker.longest_monologue_secs = speaker.longest_monologue_secs.max(chunk.end - monologue_start);
+        previous = Some((index, chunk.end));
+    }
+
+    for (speaker, (sum, count)) in speakers.iter_mut().zip(latencies) {
+        if analytics.speech_secs > 0.0 {
+            speaker.talk_share = speaker.talk_secs / analytics.speech_secs;
+        }
+        if speaker.talk_secs > 0.0 {
+            speaker.words_per_minute = f64::from(speaker.words) * 60.0 / speaker.talk_secs;
+        }
+        if count > 0 {
+            speaker.mean_answer_latency_secs = Some(sum / f64::from(count));
+        }
+    }
+    speakers.sort_by(|a, b| b.talk_secs.total_cmp(&a.talk_secs));
+    analytics.speakers = speakers;
+    analytics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speech(seq: u64, speaker: &str, start: f64, end: f64) -> TranscriptChunk {
+        TranscriptChunk::speech(seq, speaker, "one two three four", start, end)
+    }
+
+    fn speaker<'a>(analytics: &'a ConversationAnalytics, name: &str) -> &'a SpeakerAnalytics {
+        analytics.speakers.iter().find(|s| s.speaker == name).unwrap()
+    }
+
+    #[test]
+    fn talk_time_share_and_pace() {
+        let chunks = [speech(1, "Ana", 0.0, 6.0), speech(2, "Ben", 6.5, 8.5), speech(3, "Ana", 9.0, 11.0)];
+        let analytics = analyze(&chunks, 12.0);
+        assert_eq!(analytics.speech_secs, 10.0);
+        assert_eq!(analytics.speakers[0].speaker, "Ana");
+        let ana = speaker(&analytics, "Ana");
+        assert_eq!((ana.talk_secs, ana.talk_share, ana.words, ana.turns), (8.0, 0.8, 8, 2));
+        assert_eq!(ana.words_per_minute, 60.0);
+        assert_eq!(speaker(&analytics, "Ben").mean_answer_latency_secs, Some(0.5));
+        assert_eq!(analytics.silence_gaps, 0);
+    }
+
+    #[test]
+    fn silence_ends_a_monologue() {
+        let chunks = [speech(1, "Ana", 0.0, 10.0), speech(2, "Ana", 10.5, 20.0), speech(3, "Ana", 25.0, 30.0)];
+        let analytics = analyze(&chunks, 30.0);
+        let ana = speaker(&analytics, "Ana");
+        assert_eq!((ana.turns, ana.longest_monologue_secs), (2, 20.0));
+        assert_eq!((analytics.silence_gaps, analytics.silence_secs, analytics.longest_silence_secs), (1, 5.0, 5.0));
+    }
+
+    #[test]
+    fn only_long_overlaps_are_interruptions() {
+        let chunks = [
+            speech(1, "Ana", 0.0, 5.0),
+            // Backchannel: half a second over Ana
+            speech(2, "Ben", 4.5, 6.0),
+            // Cut in on for 2 s
+            speech(3, "Ana", 4.0, 8.0),
+        ];
+        let analytics = analyze(&chunks, 8.0);
+        assert_eq!(analytics.overlaps, 2);
+        assert_eq!(speaker(&analytics, "Ana").interruptions, 1);
+        assert_eq!(speaker(&analytics, "Ben").interrupted, 1);
+        assert_eq!(speaker(&analytics, "Ben").interruptions, 0);
+    }
+
+    #[test]
+    fn pauses_are_neither_silence_nor_latency() {
+        let mut pause = speech(2, "system", 5.0, 65.0);
+        pause.kind = ChunkKind::Pause;
+        let chunks = [speech(1, "Ana", 0.0, 5.0), pause, speech(3, "Ben", 66.0, 70.0)];
+        let analytics = analyze(&chunks, 70.0);
+        assert_eq!(analytics.silence_gaps, 0);
+        assert_eq!(speaker(&analytics, "Ben").mean_answer_latency_secs, None);
+        assert_eq!(analytics.speakers.len(), 2);
+    }
+}
@@ -1,108 +1,131 @@
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::SampleFormat;
 use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
 use serde_json::json;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
+use std::sync::Arc;
 use tauri::Emitter;
 use tokio::sync::mpsc;
-use tokio_tungstenite::connect_async;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::tungstenite::Message;
+use ts_rs::TS;
+
+use crate::activity::AudioSource;
+use crate::config::{self, CaptureConfig, AUTO_LANGUAGE};
+use crate::error::{CaptureError, ErrorCode};
+use crate::endpoints::{endpoint_host, stream_url, EndpointPool};
+use crate::network::{Network, WsStream};
+use crate::events::{CaptureStarted, CredentialsReason, MicStarted, ProviderSwitch, SttConnecting, SttStalled};
+use crate::protocol::{message_type, ProxyMessage, MIN_PROXY_PROTOCOL_VERSION, PROTOCOL_VERSION};
+use crate::session::{SessionShared, SessionState};
+use crate::stats::SessionStats;
+use crate::transcript::{SpeechSegment, TranscriptChunk};
 
+#[cfg(target_os = "macos")]
+use crate::events::SystemAudioStarted;
 #[cfg(target_os = "macos")]
 use screencapturekit::prelude::*;
 
-static IS_RECORDING: AtomicBool = AtomicBool::new(false);
-
-lazy_static::lazy_static! {
-    static ref STOP_TX: Mutex<Option<mpsc::Sender<()>>> = Mutex::new(None);
-}
-
 /// Target sample rate for Deepgram (16kHz mono PCM16)
 const TARGET_SAMPLE_RATE: u32 = 16000;
 const TARGET_CHANNELS: u16 = 1;
 
-pub fn get_status() -> serde_json::Value {
-    json!({ "isRecording": IS_RECORDING.load(Ordering::Acquire) })
+/// How often the stall watchdog looks at the time since the last proxy message
+const WATCHDOG_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How long the session waits for `update_capture_credentials` after an auth failure
+const CREDENTIALS_WAIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// How long a language switch waits for the provider to flush the utterance
+/// in progress before reconnecting anyway
+const FINAL_FLUSH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Drift between the stream and session clocks tolerated before realigning them
+const STREAM_CLOCK_TOLERANCE_SECS: f64 = 0.5;
+
+/// Upper bound of the delay between connection attempts
+const MAX_CONNECT_BACKOFF: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Audio held while the STT connection is down (60s of 16kHz PCM16)
+const MAX_BACKLOG_BYTES: usize = TARGET_SAMPLE_RATE as usize * 2 * 60;
+
+/// Entry of `list_audio_devices`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AudioDevice {
+    pub name: String,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
 }
 
-pub fn list_devices() -> Result<Vec<serde_json::Value>, anyhow::Error> {
+pub fn list_devices() -> Result<Vec<AudioDevice>, anyhow::Error> {
     let host = cpal::default_host();
     let mut devices = Vec::new();
-    for device in host.input_devices()? {
+    let inputs = host
+        .input_devices()
+        .map_err(|e| CaptureError::new(ErrorCode::AudioDeviceError, format!("Cannot list audio devices: {}", e)))?;
+    for device in inputs {
         if let Ok(name) = device.name() {
             let cfg = device.default_input_config().ok();
-            devices.push(json!({
-                "name": name,
-                "sampleRate": cfg.as_ref().map(|c| c.sample_rate().0),
-                "channels": cfg.as_ref().map(|c| c.channels()),
-            }));
+            devices.push(AudioDevice {
+                name,
+                sample_rate: cfg.as_ref().map(|c| c.sample_rate().0),
+                channels: cfg.as_ref().map(|c| c.channels()),
+            });
         }
     }
     Ok(devices)
 }
 
-pub async fn start_capture(
-    app: tauri::AppHandle,
-    interview_id: i64,
-    config: serde_json::Value,
-) -> Result<String, anyhow::Error> {
-    // P0 fix: atomic compare_exchange prevents race condition between concurrent calls
-    if IS_RECORDING.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_err() {
-        return Err(anyhow::anyhow!("Already recording"));
-    }
-
-    let auth_token = config["authToken"].as_str().unwrap_or("").to_string();
-    let proxy_url = config["sttProxyUrl"].as_str()
-        .unwrap_or("https://interview-stt-proxy.agusmontoya.workers.dev")
-        .to_string();
-    let language = config["language"].as_str().unwrap_or("en").to_string();
-    let provider = config["provider"].as_str().unwrap_or("deepgram").to_string();
-    let model = config["model"].as_str().unwrap_or("nova-3").to_string();
-    let supabase_url = config["supabaseUrl"].as_str().unwrap_or("").to_string();
-    let supabase_anon = config["supabaseAnonKey"].as_str().unwrap_or("").to_string();
-    let internal_key = config["internalApiKey"].as_str().unwrap_or("").to_string();
-
-    let (stop_tx, stop_rx) = mpsc::channel::<()>(1);
-    *STOP_TX.lock().unwrap() = Some(stop_tx);
-
-    // IS_RECORDING already set to true by compare_exchange above
-
-    // Mixed audio channel — both mic and system audio send PCM16 mono 16kHz here
-    let (audio_tx, audio_rx) = mpsc::channel::<Vec<u8>>(200);
+/// Start the platform's audio sources feeding `audio_tx`. The returned handles
+/// finish once the session is no longer active.
+pub fn spawn_sources(
+    audio_tx: mpsc::Sender<Vec<u8>>,
+    session: &Arc<SessionShared>,
+    app: &tauri::AppHandle,
+) -> Vec<JoinHandle<()>> {
+    let mut tasks = Vec::new();
 
     // === Audio capture strategy ===
     // macOS: ScreenCaptureKit captures BOTH system audio + mic (macOS 14+)
     // Other: cpal captures mic only (no system audio)
-    
+
     #[cfg(target_os = "macos")]
     {
         let sck_tx = audio_tx.clone();
         let app_sck = app.clone();
+        let session_sck = session.clone();
         // Try ScreenCaptureKit first (captures mic + system audio together)
-        tokio::spawn(async move {
-            match run_system_audio_capture(sck_tx.clone(), &app_sck).await {
+        tasks.push(tokio::spawn(async move {
+            match run_system_audio_capture(sck_tx.clone(), &session_sck, &app_sck).await {
                 Ok(()) => {
                     log::info!("ScreenCaptureKit capture ended normally");
                 }
                 Err(e) => {
                     log::error!("ScreenCaptureKit failed: {} — falling back to mic-only via cpal", e);
-                    let _ = app_sck.emit("capture-warning", json!({ 
-                        "message": format!("System audio unavailable ({}). Using mic only.", e),
-                        "code": "SCK_FALLBACK"
-                    }));
+                    let _ = app_sck.emit(
+                        "capture-warning",
+                        CaptureError::new(
+                            ErrorCode::SystemAudioUnavailable,
+                            "System audio unavailable. Using mic only.",
+                        )
+                        .with_context("reason", &e),
+                    );
                     // Fallback: mic-only via cpal on a blocking thread
                     let mic_tx = sck_tx;
                     let app_mic = app_sck.clone();
+                    let session_mic = session_sck.clone();
                     tokio::task::spawn_blocking(move || {
-                        if let Err(e2) = run_mic_capture(mic_tx, &app_mic) {
+                        if let Err(e2) = run_mic_capture(mic_tx, &session_mic, &app_mic) {
                             log::error!("Mic fallback also failed: {}", e2);
-                            let _ = app_mic.emit("capture-error", json!({ "error": format!("Mic: {}", e2) }));
+                            let _ = app_mic.emit("capture-error", CaptureError::from(e2));
                         }
                     }).await.ok();
                 }
             }
-        });
+        }));
     }
 
     #[cfg(not(target_os = "macos"))]
@@ -110,54 +133,32 @@ pub async fn start_capture(
         // Non-macOS: mic only via cpal
         let mic_tx = audio_tx.clone();
         let app_mic = app.clone();
-        std::thread::spawn(move || {
-            if let Err(e) = run_mic_capture(mic_tx, &app_mic) {
+        let session_mic = session.clone();
+        tasks.push(tokio::task::spawn_blocking(move || {
+            if let Err(e) = run_mic_capture(mic_tx, &session_mic, &app_mic) {
                 log::error!("Mic capture error: {}", e);
-                let _ = app_mic.emit("capture-error", json!({ "error": format!("Mic: {}", e) }));
+                let _ = app_mic.emit("capture-error", CaptureError::from(e));
             }
-        });
-        let _ = app.emit("capture-warning", json!({ 
-            "message": "System audio capture not available — using microphone only" 
         }));
+        let _ = app.emit(
+            "capture-warning",
+            CaptureError::new(
+                ErrorCode::SystemAudioUnavailable,
+                "System audio capture not available — using microphone only",
+            )
+            .with_context("platform", std::env::consts::OS),
+        );
     }
 
-    let _ = app.emit("capture-started", json!({ 
-        "mic": true, 
-        "systemAudio": cfg!(target_os = "macos") 
-    }));
+    let _ = app.emit("capture-started", CaptureStarted {
+        mic: true,
+        system_audio: cfg!(target_os = "macos"),
+    });
 
     // P0 fix: drop original audio_tx so audio_rx sees channel close when all producers finish
     drop(audio_tx);
 
-    // === WebSocket + analysis on tokio ===
-    let app_ws = app.clone();
-    tokio::spawn(async move {
-        if let Err(e) = run_websocket(
-            audio_rx, stop_rx, &auth_token, &proxy_url,
-            &language, &provider, &model,
-            &supabase_url, &supabase_anon, &internal_key,
-            interview_id, &app_ws,
-        ).await {
-            log::error!("WebSocket error: {}", e);
-            let _ = app_ws.emit("capture-error", json!({ "error": e.to_string() }));
-        }
-        IS_RECORDING.store(false, Ordering::Release);
-        let _ = app_ws.emit("capture-stopped", json!({}));
-    });
-
-    Ok("Capture started (mic + system audio)".to_string())
-}
-
-pub async fn stop_capture(_app: tauri::AppHandle) -> Result<String, anyhow::Error> {
-    if !IS_RECORDING.load(Ordering::Acquire) {
-        return Err(anyhow::anyhow!("Not recording"));
-    }
-    let tx = STOP_TX.lock().unwrap().take();
-    if let Some(tx) = tx {
-        let _ = tx.send(()).await;
-    }
-    IS_RECORDING.store(false, Ordering::Release);
-    Ok("Capture stopped".to_string())
+    tasks
 }
 
 // ============================================================================
@@ -166,64 +167,109 @@ pub async fn stop_capture(_app: tauri::AppHandle) -> Result<String, anyhow::Erro
 
 fn run_mic_capture(
     audio_tx: mpsc::Sender<Vec<u8>>,
+    session: &SessionShared,
     app: &tauri::AppHandle,
 ) -> Result<(), anyhow::Error> {
+    let stats = session.stats.clone();
+    let mutes = session.mutes.clone();
+    let (stream, device_name) = open_mic(move |samples| {
+        if mutes.is_muted(AudioSource::Mic) {
+            return;
+        }
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        send_audio(&audio_tx, &stats, bytes);
+    })?;
+    session.stats.add_source(AudioSource::Mic, Some(device_name.clone()));
+    let _ = app.emit("mic-started", MicStarted { device: device_name });
+
+    // Keep thread alive while recording
+    while session.is_active() {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    drop(stream);
+    Ok(())
+}
+
+/// Record `duration` of the default mic as 16kHz mono, e.g. for voice enrollment
+pub fn record_mic(duration: std::time::Duration) -> Result<Vec<i16>, CaptureError> {
+    let recorded = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = recorded.clone();
+    let (stream, _) = open_mic(move |samples| sink.lock().unwrap().extend_from_slice(samples))?;
+    std::thread::sleep(duration);
+    drop(stream);
+    let samples = std::mem::take(&mut *recorded.lock().unwrap());
+    Ok(samples)
+}
+
+/// Open and start the default mic, calling `on_audio` with 16kHz mono buffers
+/// from the audio thread. Capture stops when the returned stream is dropped.
+fn open_mic(mut on_audio: impl FnMut(&[i16]) + Send + 'static) -> Result<(cpal::Stream, String), CaptureError> {
     let host = cpal::default_host();
     let device = host.default_input_device()
-        .ok_or_else(|| anyhow::anyhow!("No input device found"))?;
+        .ok_or_else(|| CaptureError::new(ErrorCode::DeviceNotFound, "No microphone found"))?;
 
-    let device_name = device.name()?;
+    let device_name = device.name().map_err(|e| mic_error(false, e))?;
     log::info!("Mic device: {}", device_name);
 
-    let supported_config = device.default_input_config()?;
+    let supported_config = device.default_input_config().map_err(|e| {
+        mic_error(matches!(e, cpal::DefaultStreamConfigError::DeviceNotAvailable), e)
+    })?;
     let source_rate = supported_config.sample_rate().0;
     let source_channels = supported_config.channels();
     log::info!("Mic config: {}Hz {}ch {:?}", source_rate, source_channels, supported_config.sample_format());
 
     let stream = match supported_config.sample_format() {
-        SampleFormat::I16 => {
-            let tx = audio_tx.clone();
-            device.build_input_stream(
-                &supported_config.into(),
-                move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                    let resampled = resample_to_16k_mono_i16(data, source_rate, source_channels);
-                    let bytes: Vec<u8> = resampled.iter().flat_map(|s| s.to_le_bytes()).collect();
-                    let _ = tx.try_send(bytes);
-                },
-                |err| log::error!("Mic stream error: {}", err),
-                None,
-            )?
-        }
-        SampleFormat::F32 => {
-            let tx = audio_tx.clone();
-            device.build_input_stream(
-                &supported_config.into(),
-                move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    // Convert f32 to i16 first
-                    let i16_data: Vec<i16> = data.iter()
-                        .map(|s| (*s * 32767.0).clamp(-32768.0, 32767.0) as i16)
-                        .collect();
-                    let resampled = resample_to_16k_mono_i16(&i16_data, source_rate, source_channels);
-                    let bytes: Vec<u8> = resampled.iter().flat_map(|s| s.to_le_bytes()).collect();
-                    let _ = tx.try_send(bytes);
-                },
-                |err| log::error!("Mic stream error: {}", err),
-                None,
-            )?
+        SampleFormat::I16 => device.build_input_stream(
+            &supported_config.into(),
+            move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                on_audio(&resample_to_16k_mono_i16(data, source_rate, source_channels));
+            },
+            |err| log::error!("Mic stream error: {}", err),
+            None,
+        ),
+        SampleFormat::F32 => device.build_input_stream(
+            &supported_config.into(),
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                // Convert f32 to i16 first
+                let i16_data: Vec<i16> = data.iter()
+                    .map(|s| (*s * 32767.0).clamp(-32768.0, 32767.0) as i16)
+                    .collect();
+                on_audio(&resample_to_16k_mono_i16(&i16_data, source_rate, source_channels));
+            },
+            |err| log::error!("Mic stream error: {}", err),
+            None,
+        ),
+        fmt => {
+            return Err(CaptureError::new(ErrorCode::UnsupportedAudioFormat, "Unsupported mic sample format")
+                .with_context("format", format!("{:?}", fmt))
+                .with_context("device", &device_name));
         }
-        fmt => return Err(anyhow::anyhow!("Unsupported mic sample format: {:?}", fmt)),
-    };
-
-    stream.play()?;
-    let _ = app.emit("mic-started", json!({ "device": device_name }));
-
-    // Keep thread alive while recording
-    while IS_RECORDING.load(Ordering::Acquire) {
-        std::thread::sleep(std::time::Duration::from_millis(50));
     }
+    .map_err(|e| mic_error(matches!(e, cpal::BuildStreamError::DeviceNotAvailable), e))?;
 
-    drop(stream);
-    Ok(())
+    stream
+        .play()
+        .map_err(|e| mic_error(matches!(e, cpal::PlayStreamError::DeviceNotAvailable), e))?;
+    Ok((stream, device_name))
+}
+
+/// cpal has no dedicated variant for a denied mic — the OS refusal only shows up
+/// in the backend-specific message
+fn mic_error(device_not_available: bool, e: impl std::fmt::Display) -> CaptureError {
+    let message = e.to_string();
+    let lower = message.to_lowercase();
+    let code = if device_not_available {
+        ErrorCode::DeviceNotFound
+    } else if ["permission", "denied", "not authorized", "not permitted"]
+        .iter()
+        .any(|p| lower.contains(p))
+    {
+        ErrorCode::MicPermissionDenied
+    } else {
+        ErrorCode::AudioDeviceError
+    };
+    CaptureError::new(code, format!("Mic: {}", message))
 }
 
 // ============================================================================
@@ -233,6 +279,7 @@ fn run_mic_capture(
 #[cfg(target_os = "macos")]
 struct SystemAudioHandler {
     audio_tx: mpsc::Sender<Vec<u8>>,
+    stats: Arc<SessionStats>,
     source_rate: u32,
     source_channels: u16,
 }
@@ -274,7 +321,7 @@ impl SCStreamOutputTrait for SystemAudioHandler {
                     );
 
                     let bytes: Vec<u8> = resampled.iter().flat_map(|s| s.to_le_bytes()).collect();
-                    let _ = self.audio_tx.try_send(bytes);
+                    send_audio(&self.audio_tx, &self.stats, bytes);
                 }
             }
         }
@@ -284,6 +331,7 @@ impl SCStreamOutputTrait for SystemAudioHandler {
 #[cfg(target_os = "macos")]
 async fn run_system_audio_capture(
     audio_tx: mpsc::Sender<Vec<u8>>,
+    session: &SessionShared,
     app: &tauri::AppHandle,
 ) -> Result<(), anyhow::Error> {
     use screencapturekit::async_api::AsyncSCShareableContent;
@@ -309,20 +357,27 @@ async fn run_system_audio_capture(
     let sck_sample_rate: u32 = 48000;
     let sck_channels: u16 = 1; // mono is enough for STT
 
-    let config = SCStreamConfiguration::new()
-        // Minimal video settings (can't fully disable video in SCK)
-        .with_width(2)
-        .with_height(2)
-        .with_fps(1) // minimum fps to save resources
-        // Audio settings — capture system audio + microphone
-        .with_captures_audio(true)
-        .with_captures_microphone(true) // macOS 14+: capture mic too!
-        .with_excludes_current_process_audio(true) // exclude our own app sounds
-        .with_sample_rate(sck_sample_rate as i32)
-        .with_channel_count(sck_channels as i32);
+    // Mixed in one stream, so muting a source means reconfiguring SCK
+    let stream_config = |system_audio: bool, mic: bool| {
+        SCStreamConfiguration::new()
+            // Minimal video settings (can't fully disable video in SCK)
+            .with_width(2)
+            .with_height(2)
+            .with_fps(1) // minimum fps to save resources
+            // Audio settings — capture system audio + microphone
+            .with_captures_audio(system_audio)
+            .with_captures_microphone(mic) // macOS 14+: capture mic too!
+            .with_excludes_current_process_audio(true) // exclude our own app sounds
+            .with_sample_rate(sck_sample_rate as i32)
+            .with_channel_count(sck_channels as i32)
+    };
+    let unmuted = |source| !session.mutes.is_muted(source);
+    let mut captured = (unmuted(AudioSource::SystemAudio), unmuted(AudioSource::Mic));
+    let config = stream_config(captured.0, captured.1);
 
     let handler = SystemAudioHandler {
         audio_tx,
+        stats: session.stats.clone(),
         source_rate: sck_sample_rate,
         source_channels: sck_channels,
     };
@@ -332,16 +387,26 @@ async fn run_system_audio_capture(
     stream.start_capture()
         .map_err(|e| anyhow::anyhow!("Failed to start ScreenCaptureKit: {}", e))?;
 
-    let _ = app.emit("system-audio-started", json!({ 
-        "sampleRate": sck_sample_rate,
-        "channels": sck_channels 
-    }));
+    // SCK mixes the default mic into the same stream
+    session.stats.add_source(AudioSource::SystemAudio, None);
+    session.stats.add_source(AudioSource::Mic, None);
+    let _ = app.emit("system-audio-started", SystemAudioStarted {
+        sample_rate: sck_sample_rate,
+        channels: sck_channels,
+    });
 
     log::info!("ScreenCaptureKit system audio capture started ({}Hz {}ch)", sck_sample_rate, sck_channels);
 
-    // Keep alive while recording
-    while IS_RECORDING.load(Ordering::Acquire) {
+    // Keep alive while recording, applying mute changes
+    while session.is_active() {
         tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let wanted = (unmuted(AudioSource::SystemAudio), unmuted(AudioSource::Mic));
+        if wanted != captured {
+            match stream.update_configuration(&stream_config(wanted.0, wanted.1)) {
+                Ok(()) => captured = wanted,
+                Err(e) => log::warn!("Cannot apply mute to ScreenCaptureKit stream: {}", e),
+            }
+        }
     }
 
     stream.stop_capture()
@@ -354,6 +419,13 @@ async fn run_system_audio_capture(
 // Audio utilities
 // ============================================================================
 
+/// Queue a converted buffer without blocking the audio callback; count it if the queue is full
+fn send_audio(tx: &mpsc::Sender<Vec<u8>>, stats: &SessionStats, bytes: Vec<u8>) {
+    if let Err(mpsc::error::TrySendError::Full(_)) = tx.try_send(bytes) {
+        stats.record_dropped();
+    }
+}
+
 /// Downsample multi-channel audio to 16kHz mono PCM16
 /// Uses simple linear interpolation for resampling and channel averaging for mono
 fn resample_to_16k_mono_i16(samples: &[i16], source_rate: u32, source_channels: u16) -> Vec<i16> {
@@ -407,114 +479,439 @@ fn resample_to_16k_mono_i16(samples: &[i16], source_rate: u32, source_channels:
 // WebSocket — forward mixed audio to STT proxy
 // ============================================================================
 
-async fn run_websocket(
-    mut audio_rx: mpsc::Receiver<Vec<u8>>,
-    mut stop_rx: mpsc::Receiver<()>,
-    auth_token: &str,
-    proxy_url: &str,
-    language: &str,
-    provider: &str,
-    model: &str,
-    supabase_url: &str,
-    supabase_anon: &str,
-    internal_key: &str,
-    interview_id: i64,
+/// Audio captured while no connection is open, flushed in order once one is
+#[derive(Default)]
+struct AudioBacklog {
+    buffers: std::collections::VecDeque<Vec<u8>>,
+    bytes: usize,
+}
+
+impl AudioBacklog {
+    /// Oldest audio is dropped first once the backlog is full
+    fn push(&mut self, audio: Vec<u8>, stats: &SessionStats) {
+        self.bytes += audio.len();
+        self.buffers.push_back(audio);
+        while self.bytes > MAX_BACKLOG_BYTES {
+            match self.buffers.pop_front() {
+                Some(old) => {
+                    self.bytes -= old.len();
+                    stats.record_dropped();
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn take(&mut self) -> std::collections::VecDeque<Vec<u8>> {
+        self.bytes = 0;
+        std::mem::take(&mut self.buffers)
+    }
+}
+
+/// Receiving ends of the session's audio and stop channels, kept across connections
+struct AudioInput {
+    audio_rx: mpsc::Receiver<Vec<u8>>,
+    stop_rx: mpsc::Receiver<()>,
+    backlog: AudioBacklog,
+}
+
+impl AudioInput {
+    /// Drive `fut` while holding incoming audio in the backlog. None if stopped first.
+    async fn buffer_while<F: std::future::Future>(&mut self, fut: F, session: &SessionShared) -> Option<F::Output> {
+        tokio::pin!(fut);
+        loop {
+            tokio::select! {
+                out = &mut fut => return Some(out),
+                Some(audio) = self.audio_rx.recv() => {
+                    if !session.is_paused() { self.backlog.push(audio, &session.stats); }
+                }
+                _ = self.stop_rx.recv() => return None,
+            }
+        }
+    }
+}
+
+/// Stream audio until stopped, across as many connections as it takes:
+/// - an auth failure asks the HUD for a new token and reconnects with it
+/// - a dropped or stalled connection fails over to the next healthiest endpoint
+///   (or reconnects to the only one), until every endpoint failed to connect in a row
+pub async fn run_websocket(
+    audio_rx: mpsc::Receiver<Vec<u8>>,
+    stop_rx: mpsc::Receiver<()>,
+    config: &CaptureConfig,
+    network: &Network,
+    session: &Arc<SessionShared>,
     app: &tauri::AppHandle,
 ) -> Result<(), anyhow::Error> {
-    // Build proxy WebSocket URL — encoding=linear16&sample_rate=16000 for pre-resampled audio
-    let ws_url = format!(
-        "{}/ws?provider={}&language={}&model={}&channels={}&sample_rate={}&encoding=linear16",
-        proxy_url.replace("https://", "wss://").replace("http://", "ws://"),
-        provider, language, model, TARGET_CHANNELS, TARGET_SAMPLE_RATE
-    );
+    let mut input = AudioInput { audio_rx, stop_rx, backlog: AudioBacklog::default() };
+    let probe = EndpointPool::probe(config.endpoints(), network.http().clone());
+    let Some(mut endpoints) = input.buffer_while(probe, session).await else {
+        return Ok(());
+    };
+    session.stats.set_endpoint(endpoints.current());
+    let mut failed_connects = 0;
+    loop {
+        let auth_token = session.credentials.token();
+        let endpoint = endpoints.current().to_string();
+        // Subscribed before connecting so a switch made meanwhile is not missed
+        let mut language_rx = session.subscribe_language();
+        let language = language_rx.borrow_and_update().clone();
+        let target = ConnectTarget { endpoint: &endpoint, auth_token: &auth_token, language: &language, network };
+        let err = match connect_with_retry(&mut input, &target, config, session, app).await {
+            Ok(None) => {
+                log::info!("Stop signal received while connecting");
+                return Ok(());
+            }
+            Ok(Some(ws_stream)) => {
+                failed_connects = 0;
+                match stream_audio(ws_stream, &mut input, &mut language_rx, config, session, app).await {
+                    Ok(StreamEnd::Stopped) => return Ok(()),
+                    Ok(StreamEnd::LanguageChanged) => {
+                        log::info!("Reconnecting to STT proxy with language {}", *language_rx.borrow());
+                        continue;
+                    }
+                    Err(e) => e,
+                }
+            }
+            Err(e) => {
+                failed_connects += 1;
+                e
+            }
+        };
+        if !session.is_active() {
+            return Ok(());
+        }
+
+        if err.code == ErrorCode::SttAuthExpired {
+            let _ = app.emit("stt-auth-expired", &err);
+            // Refreshed while this connection was failing — no need to ask
+            if session.credentials.token() == auth_token
+                && !wait_for_credentials(&mut input, &auth_token, session, app).await
+            {
+                return Ok(());
+            }
+            if session.credentials.token() == auth_token {
+                return Err(err.into());
+            }
+            session.stats.record_reconnect();
+            log::info!("Reconnecting to STT proxy with refreshed credentials");
+            continue;
+        }
+
+        if !err.retryable || failed_connects >= endpoints.count() {
+            return Err(err.into());
+        }
+        let Some(failover) = input.buffer_while(endpoints.failover(), session).await else {
+            return Ok(());
+        };
+        match failover {
+            Some(next) => {
+                log::warn!("STT endpoint failover: {} → {} ({})", endpoint, next, err.message);
+                session.stats.record_endpoint_switch(&next);
+                let _ = app.emit("provider-switch", ProviderSwitch {
+                    from: endpoint_host(&endpoint),
+                    to: endpoint_host(&next),
+                    reason: err.message.clone(),
+                });
+            }
+            None => log::info!("Reconnecting to STT proxy after: {}", err.message),
+        }
+        session.stats.record_reconnect();
+    }
+}
+
+/// Buffer audio until the token changes. Returns false if the session was
+/// stopped meanwhile; on timeout the token is left unchanged.
+async fn wait_for_credentials(
+    input: &mut AudioInput,
+    rejected_token: &str,
+    session: &SessionShared,
+    app: &tauri::AppHandle,
+) -> bool {
+    let mut token_rx = session.credentials.subscribe();
+    session.request_credentials(app, CredentialsReason::AuthFailed);
+    let changed = async {
+        while token_rx.changed().await.is_ok() {
+            if *token_rx.borrow_and_update() != rejected_token {
+                return;
+            }
+        }
+    };
+    match input.buffer_while(tokio::time::timeout(CREDENTIALS_WAIT_TIMEOUT, changed), session).await {
+        None => {
+            log::info!("Stop signal received while waiting for credentials");
+            false
+        }
+        Some(Err(_)) => {
+            log::warn!("No new credentials within {:?}", CREDENTIALS_WAIT_TIMEOUT);
+            true
+        }
+        Some(Ok(())) => true,
+    }
+}
+
+/// Where and how one connection attempt goes
+struct ConnectTarget<'a> {
+    endpoint: &'a str,
+    auth_token: &'a str,
+    language: &'a str,
+    network: &'a Network,
+}
+
+/// Open the proxy WebSocket, retrying retryable failures with jittered
+/// exponential backoff and holding audio meanwhile. `Ok(None)` if stopped first.
+async fn connect_with_retry(
+    input: &mut AudioInput,
+    target: &ConnectTarget<'_>,
+    config: &CaptureConfig,
+    session: &SessionShared,
+    app: &tauri::AppHandle,
+) -> Result<Option<WsStream>, CaptureError> {
+    let max_attempts = config.connect_attempts;
+    let mut last_error = None;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let _ = app.emit("stt-connecting", SttConnecting {
+            session_id: session.id.clone(),
+            attempt,
+            max_attempts,
+            last_error: last_error.take(),
+        });
+
+        let request = ws_request(target, config)?;
+        let connect = tokio::time::timeout(config.connect_timeout(), target.network.connect_ws(request));
+        let err = match input.buffer_while(connect, session).await {
+            None => return Ok(None),
+            Some(Ok(Ok((ws_stream, _)))) => return Ok(Some(ws_stream)),
+            Some(Ok(Err(e))) => handshake_error(e, config, target.language),
+            Some(Err(_)) => CaptureError::new(
+                ErrorCode::SttUnreachable,
+                format!("WebSocket connection timeout ({}s) — STT proxy unreachable", config.connect_timeout_secs),
+            ),
+        };
+        let err = err.with_context("endpoint", target.endpoint);
+        log::warn!("STT connect attempt {}/{} to {} failed: {}", attempt, max_attempts, target.endpoint, err);
+        if !err.retryable || attempt >= max_attempts {
+            return Err(err.with_context("attempts", attempt));
+        }
+
+        let delay = backoff_delay(config.connect_backoff_ms, attempt);
+        if input.buffer_while(tokio::time::sleep(delay), session).await.is_none() {
+            return Ok(None);
+        }
+        last_error = Some(err);
+    }
+}
+
+/// Upgrade request for the proxy's `/ws` endpoint
+fn ws_request(
+    target: &ConnectTarget<'_>,
+    config: &CaptureConfig,
+) -> Result<tokio_tungstenite::tungstenite::http::Request<()>, CaptureError> {
+    let ConnectTarget { endpoint, auth_token, language, .. } = *target;
+    let invalid = |message: String| {
+        CaptureError::new(ErrorCode::InvalidConfig, format!("Invalid STT proxy URL: {}", message))
+            .with_context("endpoint", endpoint)
+    };
+    let mut url = stream_url(endpoint).map_err(invalid)?;
+    let language = config::stream_language(&config.provider, language).map_err(|message| {
+        CaptureError::new(ErrorCode::InvalidConfig, format!("language {}", message)).with_context("language", language)
+    })?;
+    // encoding=linear16&sample_rate=16000 for pre-resampled audio
+    url.query_pairs_mut()
+        .append_pair("provider", &config.provider)
+        .append_pair("language", language)
+        .append_pair("model", &config.model)
+        .append_pair("channels", &TARGET_CHANNELS.to_string())
+        .append_pair("sample_rate", &TARGET_SAMPLE_RATE.to_string())
+        .append_pair("encoding", "linear16")
+        .append_pair("protocol", &PROTOCOL_VERSION.to_string())
+        .extend_pairs(config.stt_query_options());
+
+    // Host (with any non-default port) and the upgrade headers come from the URL
+    let mut request = url.as_str().into_client_request().map_err(|e| invalid(e.to_string()))?;
+    let authorization = format!("Bearer {}", auth_token)
+        .parse()
+        .map_err(|_| CaptureError::new(ErrorCode::SttAuthExpired, "Auth token is not a valid header value"))?;
+    request.headers_mut().insert("Authorization", authorization);
+    Ok(request)
+}
+
+/// `base * 2^(attempt-1)` capped at `MAX_CONNECT_BACKOFF`, then scaled by a
+/// random 50–100% so clients restarted together do not retry in lockstep
+fn backoff_delay(base_ms: u32, attempt: u32) -> std::time::Duration {
+    let exp = u64::from(base_ms).saturating_mul(1u64 << (attempt - 1).min(16));
+    let capped = exp.min(MAX_CONNECT_BACKOFF.as_millis() as u64);
+    let jitter = 0.5 + fastrand::f64() / 2.0;
+    std::time::Duration::from_millis((capped as f64 * jitter) as u64)
+}
 
-    let request = tokio_tungstenite::tungstenite::http::Request::builder()
-        .uri(&ws_url)
-        .header("Authorization", format!("Bearer {}", auth_token))
-        .header("Host", proxy_url.replace("https://", "").replace("http://", ""))
-        .header("Connection", "Upgrade")
-        .header("Upgrade", "websocket")
-        .header("Sec-WebSocket-Version", "13")
-        .header("Sec-WebSocket-Key", tokio_tungstenite::tungstenite::handshake::client::generate_key())
-        .body(())?;
-
-    // P0 fix: 10s connection timeout prevents indefinite hang if proxy is unreachable
-    let (ws_stream, _) = tokio::time::timeout(
-        std::time::Duration::from_secs(10),
-        connect_async(request),
-    ).await
-        .map_err(|_| anyhow::anyhow!("WebSocket connection timeout (10s) — STT proxy unreachable"))??;
+/// Why `stream_audio` ended without an error
+enum StreamEnd {
+    Stopped,
+    /// `set_capture_language` — reconnect with the new language
+    LanguageChanged,
+}
+
+/// One connection to the STT proxy, from handshake until it ends
+async fn stream_audio(
+    ws_stream: WsStream,
+    input: &mut AudioInput,
+    language_rx: &mut tokio::sync::watch::Receiver<String>,
+    config: &CaptureConfig,
+    session: &Arc<SessionShared>,
+    app: &tauri::AppHandle,
+) -> Result<StreamEnd, CaptureError> {
     let (mut ws_tx, mut ws_rx) = ws_stream.split();
+    if session.state() == SessionState::Starting {
+        session.transition(app, SessionState::Running);
+    }
+    session.diarizer.start_stream();
+    let backlog = input.backlog.take();
+    let backlog_secs = audio_secs(backlog.iter().map(Vec::len).sum());
+    let now = session.transcript.lock().unwrap().elapsed();
+    let clock = Arc::new(std::sync::Mutex::new(StreamClock::new(now - backlog_secs)));
+    for audio in backlog {
+        let len = audio.len();
+        session.diarizer.push_stream(&audio);
+        ws_tx.send(Message::Binary(audio)).await.map_err(connection_lost)?;
+        session.stats.record_streamed(len);
+        clock.lock().unwrap().sent(audio_secs(len), None);
+    }
 
-    log::info!("STT proxy connected (provider: {}, language: {}, rate: {}Hz)", provider, language, TARGET_SAMPLE_RATE);
+    log::info!(
+        "STT proxy connected (provider: {}, language: {}, rate: {}Hz)",
+        config.provider, *language_rx.borrow(), TARGET_SAMPLE_RATE
+    );
 
     // Spawn transcript reader
-    let supabase_url = supabase_url.to_string();
-    let supabase_anon = supabase_anon.to_string();
-    let internal_key = internal_key.to_string();
+    let session_reader = session.clone();
     let app_clone = app.clone();
-
-    let reader = tokio::spawn(async move {
-        let client = reqwest::Client::new();
-        while let Some(Ok(msg)) = ws_rx.next().await {
-            if let Message::Text(text) = msg {
-                if let Ok(data) = serde_json::from_str::<serde_json::Value>(&text) {
-                    match data["type"].as_str() {
-                        Some("transcript") if data["is_final"] == true => {
-                            let transcript = data["text"].as_str().unwrap_or("");
-                            if transcript.is_empty() { continue; }
-
-                            let speaker = data["words"]
-                                .as_array()
-                                .and_then(|w| w.first())
-                                .and_then(|w| w["speaker"].as_i64())
-                                .unwrap_or(0);
-
-                            let chunk = json!({
-                                "speaker": format!("speaker_{}", speaker),
-                                "text": transcript,
-                                "timestamp": chrono::Utc::now().to_rfc3339(),
-                                "confidence": data["confidence"].as_f64().unwrap_or(0.9),
-                                "provider": data["provider"],
-                            });
-
-                            let _ = app_clone.emit("transcript", &chunk);
-
-                            let _ = client.post(format!("{}/functions/v1/analyze-chunk", supabase_url))
-                                .header("Authorization", format!("Bearer {}", supabase_anon))
-                                .header("x-internal-key", &internal_key)
-                                .json(&json!({ "interviewId": interview_id, "chunk": chunk }))
-                                .send()
-                                .await;
-                        }
-                        Some("provider_switch") => {
-                            log::warn!("STT provider failover: {} → {}",
-                                data["from"].as_str().unwrap_or("?"),
-                                data["to"].as_str().unwrap_or("?"));
-                            let _ = app_clone.emit("provider-switch", &data);
-                        }
-                        Some("error") => {
-                            log::error!("STT proxy error: {}", data["message"].as_str().unwrap_or("unknown"));
-                            let _ = app_clone.emit("capture-error", &data);
-                        }
-                        Some("connected") => {
-                            log::info!("STT proxy confirmed connection: provider={}", 
-                                data["provider"].as_str().unwrap_or("?"));
-                        }
-                        _ => {}
-                    }
+    let clock_reader = clock.clone();
+
+    // Pongs only prove the socket is alive — an edge runtime answers them
+    // even when the provider behind it has stalled — so the stall watchdog
+    // goes by proxy messages
+    let liveness = Arc::new(std::sync::Mutex::new(Liveness::now()));
+    let liveness_reader = liveness.clone();
+
+    // Resolves with the reason the proxy ended the stream
+    let mut reader = tokio::spawn(async move {
+        loop {
+            let next = ws_rx.next().await;
+            let message = matches!(next, Some(Ok(Message::Text(_) | Message::Binary(_))));
+            liveness_reader.lock().unwrap().received(message);
+            let text = match next {
+                Some(Ok(Message::Text(text))) => text,
+                Some(Ok(Message::Close(frame))) => return close_error(frame.as_ref()),
+                // tungstenite queues the Pong itself; it goes out with the next write
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => continue,
+                Some(Ok(Message::Binary(data))) => {
+                    log::warn!("Unexpected binary message from STT proxy ({} bytes)", data.len());
+                    session_reader.stats.record_unknown_message();
+                    continue;
                 }
+                Some(Err(e)) => return connection_lost(e),
+                None => {
+                    return CaptureError::new(ErrorCode::SttConnectionLost, "STT proxy closed the connection")
+                }
+            };
+            if let Err(err) = handle_proxy_message(&text, &session_reader, &clock_reader, &app_clone) {
+                return err;
             }
         }
     });
 
-    // Main loop: forward audio to proxy
+    let keepalive_interval = config.keepalive_interval();
+    let stall_timeout = config.stall_timeout();
+    let mut keepalive = tokio::time::interval(keepalive_interval);
+    let mut watchdog = tokio::time::interval(WATCHDOG_INTERVAL);
+    let mut last_audio_sent: Option<std::time::Instant> = None;
+
+    // Main loop: forward audio to proxy (dropped while paused)
     loop {
         tokio::select! {
-            Some(audio) = audio_rx.recv() => {
-                if ws_tx.send(Message::Binary(audio)).await.is_err() { break; }
+            Some(audio) = input.audio_rx.recv() => {
+                if session.is_paused() { continue; }
+                let len = audio.len();
+                let captured = session.transcript.lock().unwrap().elapsed();
+                session.diarizer.push_stream(&audio);
+                if let Err(e) = ws_tx.send(Message::Binary(audio)).await {
+                    reader.abort();
+                    return Err(connection_lost(e));
+                }
+                session.stats.record_streamed(len);
+                clock.lock().unwrap().sent(audio_secs(len), Some(captured));
+                last_audio_sent = Some(std::time::Instant::now());
+            }
+            _ = keepalive.tick() => {
+                // Providers close streams that get no audio for ~10s; the Ping
+                // makes the proxy answer even when nobody speaks
+                let idle = last_audio_sent.map_or(true, |t| t.elapsed() >= keepalive_interval);
+                let mut sent = Ok(());
+                if idle {
+                    sent = ws_tx.send(Message::Text(json!({ "type": "KeepAlive" }).to_string())).await;
+                }
+                if sent.is_ok() {
+                    sent = ws_tx.send(Message::Ping(Vec::new())).await;
+                }
+                if let Err(e) = sent {
+                    reader.abort();
+                    return Err(connection_lost(e));
+                }
+            }
+            _ = watchdog.tick() => {
+                let (silent, unanswered) = {
+                    let liveness = liveness.lock().unwrap();
+                    (liveness.message.elapsed(), liveness.frame.elapsed())
+                };
+                if unanswered >= stall_timeout {
+                    log::warn!("STT proxy answered no ping for {:.0}s — reconnecting", unanswered.as_secs_f64());
+                    reader.abort();
+                    return Err(CaptureError::new(ErrorCode::SttConnectionLost, "STT proxy stopped answering pings")
+                        .with_context("silentSecs", unanswered.as_secs()));
+                }
+                let audio_flowing = last_audio_sent.is_some_and(|t| t.elapsed() < stall_timeout);
+                if audio_flowing && silent >= stall_timeout {
+                    log::warn!("STT proxy silent for {:.0}s while streaming — reconnecting", silent.as_secs_f64());
+                    let _ = app.emit("stt-stalled", SttStalled {
+                        session_id: session.id.clone(),
+                        silent_secs: silent.as_secs_f64(),
+                        stall_timeout_secs: config.stall_timeout_secs,
+                    });
+                    reader.abort();
+                    return Err(CaptureError::new(ErrorCode::SttStalled, "STT proxy stopped responding")
+                        .with_context("silentSecs", silent.as_secs()));
+                }
+            }
+            ended = &mut reader => {
+                let err = ended.unwrap_or_else(|e| {
+                    CaptureError::new(ErrorCode::Internal, format!("Transcript reader crashed: {}", e))
+                });
+                log::warn!("STT stream ended by proxy: {} ({:?})", err.message, err.code);
+                return Err(err);
             }
-            _ = stop_rx.recv() => {
+            Ok(()) = language_rx.changed() => {
+                // The provider flushes the utterance in progress and closes;
+                // audio arriving meanwhile is buffered for the reconnect
+                let _ = ws_tx.send(Message::Text(json!({ "type": "CloseStream" }).to_string())).await;
+                let flushed = tokio::time::timeout(FINAL_FLUSH_TIMEOUT, &mut reader);
+                match input.buffer_while(flushed, session).await {
+                    None => {
+                        reader.abort();
+                        return Ok(StreamEnd::Stopped);
+                    }
+                    Some(Err(_)) => {
+                        log::warn!("STT proxy did not close within {:?} of the language switch", FINAL_FLUSH_TIMEOUT)
+                    }
+                    Some(Ok(_)) => {}
+                }
+                let _ = ws_tx.close().await;
+                reader.abort();
+                return Ok(StreamEnd::LanguageChanged);
+            }
+            _ = input.stop_rx.recv() => {
                 log::info!("Stop signal received");
                 let _ = ws_tx.close().await;
                 break;
@@ -523,5 +920,396 @@ async fn run_websocket(
     }
 
     reader.abort();
+    Ok(StreamEnd::Stopped)
+}
+
+/// When the proxy last sent anything, and last sent a message (not a control frame)
+struct Liveness {
+    frame: std::time::Instant,
+    message: std::time::Instant,
+}
+
+impl Liveness {
+    fn now() -> Self {
+        let now = std::time::Instant::now();
+        Self { frame: now, message: now }
+    }
+
+    fn received(&mut self, message: bool) {
+        self.frame = std::time::Instant::now();
+        if message {
+            self.message = self.frame;
+        }
+    }
+}
+
+/// Maps the provider's clock — seconds of audio sent on the connection — to
+/// session offsets. No audio is sent while paused, so the stream clock falls
+/// behind the session's at every pause; live audio realigns it.
+struct StreamClock {
+    /// Stream seconds sent so far
+    sent: f64,
+    /// (stream seconds, session offset) pairs where the clocks were aligned, oldest first
+    anchors: Vec<(f64, f64)>,
+}
+
+impl StreamClock {
+    /// Stream time zero is at session offset `origin`
+    fn new(origin: f64) -> Self {
+        Self { sent: 0.0, anchors: vec![(0.0, origin)] }
+    }
+
+    /// Account for `secs` of audio sent, captured until session offset
+    /// `captured` if known (live audio), else right after the previous audio
+    fn sent(&mut self, secs: f64, captured: Option<f64>) {
+        if let Some(captured) = captured {
+            let start = captured - secs;
+            if (start - self.session_offset(self.sent)).abs() > STREAM_CLOCK_TOLERANCE_SECS {
+                self.anchors.push((self.sent, start));
+            }
+        }
+        self.sent += secs;
+    }
+
+    fn session_offset(&self, stream_secs: f64) -> f64 {
+        let (anchor_stream, anchor_session) = self
+            .anchors
+            .iter()
+            .rev()
+            .find(|(s, _)| *s <= stream_secs)
+            .unwrap_or(&self.anchors[0]);
+        anchor_session + (stream_secs - anchor_stream)
+    }
+}
+
+/// Seconds of 16kHz PCM16 audio in `bytes`
+fn audio_secs(bytes: usize) -> f64 {
+    bytes as f64 / (2.0 * f64::from(TARGET_SAMPLE_RATE))
+}
+
+/// Act on one text message from the proxy. An error ends the connection.
+fn handle_proxy_message(
+    text: &str,
+    session: &SessionShared,
+    clock: &std::sync::Mutex<StreamClock>,
+    app: &tauri::AppHandle,
+) -> Result<(), CaptureError> {
+    let msg = match ProxyMessage::parse(text) {
+        Ok(msg) => msg,
+        Err(e) => {
+            log::warn!("Malformed STT proxy message ({}): {:.200}", e, text);
+            session.stats.record_malformed_message();
+            return Ok(());
+        }
+    };
+
+    match msg {
+        ProxyMessage::Connected { provider, protocol_version } => {
+            let version = protocol_version.unwrap_or(1);
+            log::info!(
+                "STT proxy confirmed connection: provider={} protocol=v{}",
+                provider.as_deref().unwrap_or("?"),
+                version
+            );
+            session.stats.set_protocol_version(version);
+            if let Some(provider) = provider {
+                session.stats.set_provider(&provider);
+            }
+            if version < MIN_PROXY_PROTOCOL_VERSION {
+                return Err(CaptureError::new(
+                    ErrorCode::SttRejected,
+                    format!("STT proxy protocol v{} is no longer supported", version),
+                )
+                .with_context("protocolVersion", version));
+            }
+            if version > PROTOCOL_VERSION {
+                log::info!(
+                    "STT proxy speaks protocol v{} (app v{}) — newer messages will be skipped",
+                    version, PROTOCOL_VERSION
+                );
+            }
+        }
+        ProxyMessage::Transcript(t) if t.is_final && !t.text.is_empty() => {
+            let session_language = session.language();
+            for turn in t.turns().into_iter().filter(|turn| !turn.text.trim().is_empty()) {
+                let language =
+                    turn.language.or_else(|| (session_language != AUTO_LANGUAGE).then(|| session_language.clone()));
+                let voice = turn
+                    .stream_span
+                    .map(|(start, end)| session.diarizer.identify(start, end, turn.speaker.is_some()))
+                    .unwrap_or_default();
+                let speaker_id = match voice.speaker {
+                    Some(guess) => format!("local_{}", guess.speaker),
+                    None => format!("speaker_{}", turn.speaker.unwrap_or(0)),
+                };
+                let mut transcript = session.transcript.lock().unwrap();
+                // Provider timestamps restart on every connection; arrival time
+                // stands in when there are none
+                let (start, end) = match turn.stream_span {
+                    Some((start, end)) => {
+                        let clock = clock.lock().unwrap();
+                        (clock.session_offset(start), clock.session_offset(end))
+                    }
+                    None => {
+                        let end = transcript.elapsed();
+                        (end - turn.duration, end)
+                    }
+                };
+                let chunk = transcript.push(SpeechSegment {
+                    speaker_id,
+                    speaker_confidence: voice.speaker.map(|guess| guess.confidence),
+                    voice_role: voice.role,
+                    text: turn.text,
+                    start,
+                    end,
+                    provider_timed: turn.stream_span.is_some(),
+                    confidence: t.confidence.unwrap_or(0.9),
+                    provider: t.provider.clone(),
+                    language,
+                });
+                drop(transcript);
+                session.publish(app, chunk);
+            }
+        }
+        // Interim results are not used yet
+        ProxyMessage::Transcript(_) => {}
+        ProxyMessage::ProviderSwitch(switch) => {
+            log::warn!("STT provider failover: {} → {}", switch.from, switch.to);
+            session.stats.record_provider_switch(&switch.to);
+            let _ = app.emit("provider-switch", switch);
+        }
+        ProxyMessage::Error { message, code } => {
+            let message = message.unwrap_or_else(|| "unknown".to_string());
+            log::error!("STT proxy error: {}", message);
+            let mut err = CaptureError::new(ErrorCode::SttProviderError, message);
+            if let Some(code) = code {
+                err = err.with_context("providerCode", code);
+            }
+            let _ = app.emit("capture-error", err);
+        }
+        ProxyMessage::Unknown => {
+            log::debug!("Skipping unknown STT proxy message type {:?}", message_type(text));
+            session.stats.record_unknown_message();
+        }
+    }
     Ok(())
 }
+
+// ============================================================================
+// STT failure classification
+// ============================================================================
+
+/// Map a failed WebSocket upgrade to an actionable error. The proxy answers
+/// 401 for an expired/invalid Clerk JWT, 429 when the quota is exhausted and
+/// 400 when the provider rejects the stream settings.
+fn handshake_error(e: tokio_tungstenite::tungstenite::Error, config: &CaptureConfig, language: &str) -> CaptureError {
+    use tokio_tungstenite::tungstenite::Error;
+    let response = match &e {
+        Error::Http(response) => response,
+        _ => {
+            return CaptureError::new(ErrorCode::SttUnreachable, format!("Cannot connect to STT proxy: {}", e));
+        }
+    };
+
+    let status = response.status();
+    let detail = response
+        .body()
+        .as_deref()
+        .map(proxy_error_detail)
+        .filter(|d| !d.is_empty());
+    let err = match status.as_u16() {
+        401 => CaptureError::new(
+            ErrorCode::SttAuthExpired,
+            "Your session expired — sign in again to resume transcription",
+        ),
+        403 => CaptureError::new(
+            ErrorCode::SttRejected,
+            "This account is not allowed to use live transcription",
+        ),
+        429 => {
+            let err = CaptureError::new(
+                ErrorCode::SttQuotaExceeded,
+                "Transcription quota exhausted or rate limited — try again later",
+            );
+            match response.headers().get("retry-after").and_then(|v| v.to_str().ok()) {
+                Some(retry_after) => err.with_context("retryAfter", retry_after),
+                None => err,
+            }
+        }
+        400 | 422 => CaptureError::new(
+            ErrorCode::SttRejected,
+            format!(
+                "The STT provider rejected the stream settings (provider {}, model {}, language {})",
+                config.provider, config.model, language
+            ),
+        )
+        .with_context("provider", &config.provider)
+        .with_context("model", &config.model)
+        .with_context("language", language),
+        _ => CaptureError::new(ErrorCode::SttUnreachable, format!("STT proxy unavailable ({})", status)),
+    };
+    let err = err.with_context("status", status.as_u16());
+    match detail {
+        Some(detail) => err.with_context("detail", detail),
+        None => err,
+    }
+}
+
+/// The proxy replies with `{ "error": "...", "message": "..." }` or plain text
+fn proxy_error_detail(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    match serde_json::from_str::<serde_json::Value>(&text) {
+        Ok(data) => data["message"]
+            .as_str()
+            .or_else(|| data["error"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        Err(_) => text.trim().chars().take(200).collect(),
+    }
+}
+
+/// Map the proxy's close frame. Standard codes cover generic failures; the
+/// proxy mirrors HTTP statuses in the 4000 range (4401 auth, 4403 forbidden,
+/// 4429 quota, 4400 bad params). Only auth failures ask for a new token —
+/// a forbidden or policy-closed session would just loop on fresh ones.
+fn close_error(frame: Option<&tokio_tungstenite::tungstenite::protocol::CloseFrame<'_>>) -> CaptureError {
+    let Some(frame) = frame else {
+        return CaptureError::new(ErrorCode::SttConnectionLost, "STT proxy closed the connection");
+    };
+    let code = u16::from(frame.code);
+    let reason = frame.reason.to_string();
+    let err = match code {
+        4001 | 4401 => CaptureError::new(
+            ErrorCode::SttAuthExpired,
+            "Your session expired — sign in again to resume transcription",
+        ),
+        4403 => CaptureError::new(
+            ErrorCode::SttRejected,
+            "This account is not allowed to use live transcription",
+        ),
+        1008 => CaptureError::new(
+            ErrorCode::SttRejected,
+            "The STT proxy closed the stream for violating its policy",
+        ),
+        4029 | 4429 => CaptureError::new(
+            ErrorCode::SttQuotaExceeded,
+            "Transcription quota exhausted — the stream was closed",
+        ),
+        1003 | 1007 | 4000 | 4400 => CaptureError::new(
+            ErrorCode::SttRejected,
+            "The STT provider rejected the audio stream",
+        ),
+        1011 | 1013 | 1014 => CaptureError::new(
+            ErrorCode::SttProviderError,
+            "The STT provider failed — reconnect to continue",
+        ),
+        _ => CaptureError::new(ErrorCode::SttConnectionLost, "STT proxy closed the connection"),
+    };
+    let err = err.with_context("closeCode", code);
+    if reason.is_empty() { err } else { err.with_context("reason", reason) }
+}
+
+fn connection_lost(e: tokio_tungstenite::tungstenite::Error) -> CaptureError {
+    CaptureError::new(ErrorCode::SttConnectionLost, format!("Connection to STT proxy lost: {}", e))
+}
+
+// ============================================================================
+// Analysis — deliver final chunks to the analyze-chunk Edge Function
+// ============================================================================
+
+/// Post queued chunks in order. Returns once the session closes the queue and it is drained.
+/// The first failure of a streak is surfaced as a `capture-warning`.
+pub async fn run_analysis(
+    mut chunk_rx: mpsc::UnboundedReceiver<TranscriptChunk>,
+    config: &CaptureConfig,
+    network: &Network,
+    session: &SessionShared,
+    app: &tauri::AppHandle,
+) {
+    let client = network.http();
+    let url = config.analyze_chunk_url();
+    let mut failing = false;
+    while let Some(chunk) = chunk_rx.recv().await {
+        let result = client.post(&url)
+            .header("Authorization", format!("Bearer {}", config.supabase_anon_key))
+            .header("x-internal-key", &config.internal_api_key)
+            .json(&json!({ "interviewId": session.interview_id, "chunk": chunk }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status());
+        session.stats.analysis_done(result.is_ok());
+        match result {
+            Ok(_) => failing = false,
+            Err(e) => {
+                log::warn!("analyze-chunk delivery failed (seq {}): {}", chunk.seq, e);
+                if !failing {
+                    let _ = app.emit("capture-warning", analysis_error(&e).with_context("seq", chunk.seq));
+                }
+                failing = true;
+            }
+        }
+    }
+}
+
+/// 4xx means analyze-chunk refused the payload or our keys; anything else is transient
+fn analysis_error(e: &reqwest::Error) -> CaptureError {
+    match e.status() {
+        Some(status) if status.is_client_error() => {
+            CaptureError::new(ErrorCode::AnalysisRejected, format!("Live analysis rejected the transcript: {}", status))
+                .with_context("status", status.as_u16())
+        }
+        Some(status) => CaptureError::new(ErrorCode::AnalysisUnreachable, format!("Live analysis unavailable: {}", status))
+            .with_context("status", status.as_u16()),
+        None => CaptureError::new(ErrorCode::AnalysisUnreachable, format!("Live analysis unreachable: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_tungstenite::tungstenite::protocol::{frame::coding::CloseCode, CloseFrame};
+
+    fn closed_with(code: u16) -> ErrorCode {
+        close_error(Some(&CloseFrame { code: CloseCode::from(code), reason: "".into() })).code
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_with_jitter() {
+        for attempt in 1..=3 {
+            let full = 500u64 << (attempt - 1);
+            let delay = backoff_delay(500, attempt).as_millis() as u64;
+            assert!((full / 2..=full).contains(&delay), "attempt {} waited {} ms", attempt, delay);
+        }
+        let cap = MAX_CONNECT_BACKOFF.as_millis() as u64;
+        assert!(backoff_delay(500, 30).as_millis() as u64 <= cap);
+        let delays: std::collections::HashSet<_> = (0..20).map(|_| backoff_delay(1000, 4)).collect();
+        assert!(delays.len() > 1, "jitter is constant");
+    }
+
+    #[test]
+    fn only_auth_close_codes_ask_for_new_credentials() {
+        assert_eq!(closed_with(4401), ErrorCode::SttAuthExpired);
+        assert_eq!(closed_with(4001), ErrorCode::SttAuthExpired);
+        assert_eq!(closed_with(4403), ErrorCode::SttRejected);
+        assert_eq!(closed_with(1008), ErrorCode::SttRejected);
+        assert_eq!(closed_with(4429), ErrorCode::SttQuotaExceeded);
+        assert_eq!(closed_with(1000), ErrorCode::SttConnectionLost);
+        assert_eq!(close_error(None).code, ErrorCode::SttConnectionLost);
+    }
+
+    #[test]
+    fn stream_clock_realigns_after_pauses() {
+        // 2 s of backlog sent on connecting at session offset 12
+        let mut clock = StreamClock::new(10.0);
+        clock.sent(2.0, None);
+        assert_eq!(clock.session_offset(0.5), 10.5);
+        // Live audio keeps the alignment despite a little jitter
+        clock.sent(1.0, Some(13.2));
+        assert_eq!(clock.anchors.len(), 1);
+        assert_eq!(clock.session_offset(2.5), 12.5);
+        // Resumed after a 30 s pause
+        clock.sent(1.0, Some(44.0));
+        assert_eq!(clock.session_offset(3.5), 43.5);
+        assert_eq!(clock.session_offset(1.0), 11.0);
+    }
+}
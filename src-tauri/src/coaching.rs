@@ -0,0 +1,342 @@
+//! Interviewer coaching nudges: org-defined thresholds on the conversation
+//! analytics, passed as `coachingRules` to `start_capture` and checked every
+//! few seconds while recording. A rule that holds emits `coaching-nudge`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use ts_rs::TS;
+
+use crate::analytics::{self, SpeakerAnalytics};
+use crate::speakers::SpeakerRole;
+use crate::transcript::{ChunkKind, TranscriptChunk};
+
+/// No nudge follows another within this long, whatever the rules say
+const MIN_NUDGE_GAP_SECS: f64 = 30.0;
+
+/// Talk share is not judged on less speech than this
+const MIN_SHARE_SPEECH_SECS: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum CoachingMetric {
+    /// 0–1 of the speech in the window
+    TalkShare,
+    /// Seconds since the speaker last spoke (since the start or a resume if never)
+    SecsSinceSpeech,
+    /// Times the speaker cut in on someone in the window
+    Interruptions,
+    /// Longest monologue in the window
+    MonologueSecs,
+    WordsPerMinute,
+    /// Mean answer latency in the window
+    AnswerLatencySecs,
+}
+
+/// One entry of `coachingRules`, e.g. `{ "id": "talk-share", "metric": "talkShare",
+/// "role": "interviewer", "above": 0.4, "windowSecs": 300, "message": "Let the candidate talk" }`
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CoachingRule {
+    #[serde(default)]
+    pub id: String,
+    pub metric: CoachingMetric,
+    /// Whose speech the metric is about; everyone's when unset
+    #[serde(default)]
+    #[ts(optional)]
+    pub role: Option<SpeakerRole>,
+    /// Fires when the metric is greater than this
+    #[serde(default)]
+    #[ts(optional)]
+    pub above: Option<f64>,
+    /// Fires when the metric is less than this
+    #[serde(default)]
+    #[ts(optional)]
+    pub below: Option<f64>,
+    /// Trailing window the metric is computed over; the whole session when unset
+    #[serde(default)]
+    #[ts(optional)]
+    pub window_secs: Option<u32>,
+    /// Shown to the interviewer
+    #[serde(default)]
+    pub message: String,
+    /// Minimum time between two nudges of this rule
+    #[serde(default = "default_cooldown_secs")]
+    #[ts(as = "Option<u32>", optional)]
+    pub cooldown_secs: u32,
+}
+
+fn default_cooldown_secs() -> u32 {
+    120
+}
+
+/// `coaching-nudge`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CoachingNudge {
+    pub rule_id: String,
+    pub message: String,
+    pub metric: CoachingMetric,
+    pub role: Option<SpeakerRole>,
+    pub value: f64,
+    /// The `above` or `below` bound that was crossed
+    pub threshold: f64,
+    /// Session offset in seconds
+    pub at: f64,
+    pub timestamp: String,
+}
+
+impl CoachingRule {
+    /// Problems with the rule, for `CaptureConfig::validate`
+    pub fn check(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("id is required".to_string());
+        }
+        if self.message.trim().is_empty() {
+            return Err("message is required".to_string());
+        }
+        if self.above.is_none() && self.below.is_none() {
+            return Err("needs above or below".to_string());
+        }
+        if self.window_secs == Some(0) {
+            return Err("windowSecs must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Crossed bound, if the rule holds for `value`
+    fn crossed(&self, value: f64) -> Option<f64> {
+        self.above.filter(|&above| value > above).or_else(|| self.below.filter(|&below| value < below))
+    }
+}
+
+/// Rules of a session and when each last fired
+pub struct Coach {
+    rules: Vec<CoachingRule>,
+    /// Session offset of each rule's last nudge, and of the last nudge overall
+    fired: Mutex<(HashMap<String, f64>, Option<f64>)>,
+}
+
+impl Coach {
+    pub fn new(rules: Vec<CoachingRule>) -> Self {
+        Self { rules, fired: Mutex::new((HashMap::new(), None)) }
+    }
+
+    /// Nudge for the first rule that holds at `elapsed` and is out of its
+    /// cooldown; earlier rules win when several hold at once
+    pub fn evaluate(&self, chunks: &[TranscriptChunk], elapsed: f64) -> Option<CoachingNudge> {
+        if self.rules.is_empty() {
+            return None;
+        }
+        let mut fired = self.fired.lock().unwrap();
+        let (by_rule, last) = &mut *fired;
+        if last.is_some_and(|at| elapsed - at < MIN_NUDGE_GAP_SECS) {
+            return None;
+        }
+        for rule in &self.rules {
+            if by_rule.get(&rule.id).is_some_and(|&at| elapsed - at < f64::from(rule.cooldown_secs)) {
+                continue;
+            }
+            let Some(value) = metric_value(rule, chunks, elapsed) else {
+                continue;
+            };
+            let Some(threshold) = rule.crossed(value) else {
+                continue;
+            };
+            by_rule.insert(rule.id.clone(), elapsed);
+            *last = Some(elapsed);
+            log::info!("Coaching rule {} fired ({:?} = {:.2})", rule.id, rule.metric, value);
+            return Some(CoachingNudge {
+                rule_id: rule.id.clone(),
+                message: rule.message.clone(),
+                metric: rule.metric,
+                role: rule.role,
+                value,
+                threshold,
+                at: elapsed,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+        None
+    }
+}
+
+/// Value of the rule's metric, None while there is not enough to judge
+fn metric_value(rule: &CoachingRule, chunks: &[TranscriptChunk], elapsed: f64) -> Option<f64> {
+    let matches_role = |role: Option<SpeakerRole>| rule.role.map_or(true, |r| role == Some(r));
+
+    if rule.metric == CoachingMetric::SecsSinceSpeech {
+        // Same as talk share: a role's silence says nothing until someone has a role
+        let roles_known =
+            rule.role.is_none() || chunks.iter().any(|c| c.kind == ChunkKind::Speech && c.role.is_some());
+        if !roles_known {
+            return None;
+        }
+        let since = chunks
+            .iter()
+            .rev()
+            .find(|c| c.kind == ChunkKind::Pause || (c.kind == ChunkKind::Speech && matches_role(c.role)))
+            .map_or(0.0, |c| c.end);
+        return Some(elapsed - since);
+    }
+
+    // Chunks are ordered by seq, not by end, so filter instead of bisecting
+    let horizon = rule.window_secs.map_or(f64::NEG_INFINITY, |w| elapsed - f64::from(w));
+    let chunks: Vec<TranscriptChunk> = chunks.iter().filter(|c| c.end >= horizon).cloned().collect();
+    let chunks = chunks.as_slice();
+    let window = analytics::analyze(chunks, elapsed);
+    let speakers: Vec<&SpeakerAnalytics> = window.speakers.iter().filter(|s| matches_role(s.role)).collect();
+    let talk_secs: f64 = speakers.iter().map(|s| s.talk_secs).sum();
+    match rule.metric {
+        // A role's share says nothing until someone in the window has a role
+        CoachingMetric::TalkShare => {
+            let roles_known = rule.role.is_none() || window.speakers.iter().any(|s| s.role.is_some());
+            (roles_known && window.speech_secs >= MIN_SHARE_SPEECH_SECS).then(|| talk_secs / window.speech_secs)
+        }
+        // Overlaps are only real between chunks timed by the provider
+        CoachingMetric::Interruptions => {
+            let timed = chunks.iter().filter(|c| c.kind == ChunkKind::Speech).all(|c| c.provider_timed);
+            timed.then(|| speakers.iter().map(|s| f64::from(s.interruptions)).sum())
+        }
+        CoachingMetric::MonologueSecs => speakers.iter().map(|s| s.longest_monologue_secs).reduce(f64::max),
+        CoachingMetric::WordsPerMinute => {
+            let words: u32 = speakers.iter().map(|s| s.words).sum();
+            (talk_secs > 0.0).then(|| f64::from(words) * 60.0 / talk_secs)
+        }
+        CoachingMetric::AnswerLatencySecs => {
+            let latencies: Vec<f64> = speakers.iter().filter_map(|s| s.mean_answer_latency_secs).collect();
+            (!latencies.is_empty()).then(|| latencies.iter().sum::<f64>() / latencies.len() as f64)
+        }
+        CoachingMetric::SecsSinceSpeech => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, metric: CoachingMetric, above: Option<f64>, below: Option<f64>) -> CoachingRule {
+        CoachingRule {
+            id: id.to_string(),
+            metric,
+            role: None,
+            above,
+            below,
+            window_secs: None,
+            message: format!("{} fired", id),
+            cooldown_secs: 120,
+        }
+    }
+
+    fn speech(seq: u64, role: SpeakerRole, start: f64, end: f64) -> TranscriptChunk {
+        let speaker = if role == SpeakerRole::Interviewer { "Ana" } else { "Ben" };
+        let mut chunk = TranscriptChunk::speech(seq, speaker, "word word word", start, end);
+        chunk.role = Some(role);
+        chunk
+    }
+
+    /// Interviewer talking 40 of 50 s
+    fn interviewer_heavy() -> Vec<TranscriptChunk> {
+        vec![
+            speech(1, SpeakerRole::Interviewer, 0.0, 20.0),
+            speech(2, SpeakerRole::Candidate, 20.5, 30.5),
+            speech(3, SpeakerRole::Interviewer, 31.0, 51.0),
+        ]
+    }
+
+    #[test]
+    fn thresholds_are_strict() {
+        let talk_share = CoachingRule {
+            role: Some(SpeakerRole::Interviewer),
+            ..rule("share", CoachingMetric::TalkShare, Some(0.8), None)
+        };
+        assert!(Coach::new(vec![talk_share.clone()]).evaluate(&interviewer_heavy(), 52.0).is_none());
+
+        let nudge = Coach::new(vec![CoachingRule { above: Some(0.7), ..talk_share }])
+            .evaluate(&interviewer_heavy(), 52.0)
+            .expect("0.8 is above 0.7");
+        assert_eq!((nudge.rule_id.as_str(), nudge.value, nudge.threshold), ("share", 0.8, 0.7));
+
+        let quiet = rule("quiet", CoachingMetric::SecsSinceSpeech, None, Some(5.0));
+        assert!(Coach::new(vec![quiet]).evaluate(&interviewer_heavy(), 52.0).is_some());
+    }
+
+    #[test]
+    fn talk_share_needs_enough_speech_and_known_roles() {
+        let share = CoachingRule {
+            role: Some(SpeakerRole::Interviewer),
+            ..rule("share", CoachingMetric::TalkShare, None, Some(0.5))
+        };
+        let short = [speech(1, SpeakerRole::Candidate, 0.0, 10.0)];
+        assert!(metric_value(&share, &short, 10.0).is_none());
+
+        let mut unlabeled = interviewer_heavy();
+        unlabeled.iter_mut().for_each(|c| c.role = None);
+        assert!(metric_value(&share, &unlabeled, 52.0).is_none());
+        assert_eq!(metric_value(&share, &interviewer_heavy(), 52.0), Some(0.8));
+    }
+
+    #[test]
+    fn secs_since_speech_needs_known_roles() {
+        let quiet = CoachingRule {
+            role: Some(SpeakerRole::Candidate),
+            ..rule("quiet", CoachingMetric::SecsSinceSpeech, Some(5.0), None)
+        };
+        let mut chunks = interviewer_heavy();
+        assert_eq!(metric_value(&quiet, &chunks, 52.0), Some(21.5));
+        chunks.iter_mut().for_each(|c| c.role = None);
+        assert_eq!(metric_value(&quiet, &chunks, 52.0), None);
+    }
+
+    #[test]
+    fn window_keeps_chunks_ending_late_out_of_order() {
+        let share = CoachingRule {
+            role: Some(SpeakerRole::Interviewer),
+            window_secs: Some(10),
+            ..rule("share", CoachingMetric::TalkShare, None, Some(0.5))
+        };
+        // A long turn finalized before a short one that ended earlier
+        let chunks = [speech(1, SpeakerRole::Candidate, 0.0, 60.0), speech(2, SpeakerRole::Interviewer, 10.0, 20.0)];
+        assert_eq!(metric_value(&share, &chunks, 60.0), Some(0.0));
+    }
+
+    #[test]
+    fn interruptions_need_provider_timing() {
+        let interruptions = rule("cut-in", CoachingMetric::Interruptions, Some(0.0), None);
+        let mut chunks = vec![
+            speech(1, SpeakerRole::Candidate, 0.0, 10.0),
+            speech(2, SpeakerRole::Interviewer, 8.0, 12.0),
+        ];
+        assert_eq!(metric_value(&interruptions, &chunks, 12.0), Some(1.0));
+        chunks[1].provider_timed = false;
+        assert_eq!(metric_value(&interruptions, &chunks, 12.0), None);
+    }
+
+    #[test]
+    fn cooldown_and_global_gap() {
+        let coach = Coach::new(vec![
+            CoachingRule { cooldown_secs: 60, ..rule("first", CoachingMetric::SecsSinceSpeech, Some(5.0), None) },
+            rule("second", CoachingMetric::SecsSinceSpeech, Some(5.0), None),
+        ]);
+        let chunks = [speech(1, SpeakerRole::Candidate, 0.0, 10.0)];
+        assert_eq!(coach.evaluate(&chunks, 20.0).map(|n| n.rule_id), Some("first".to_string()));
+        // Nothing within 30 s of the last nudge
+        assert!(coach.evaluate(&chunks, 45.0).is_none());
+        // First is cooling down, so the next rule that holds fires
+        assert_eq!(coach.evaluate(&chunks, 55.0).map(|n| n.rule_id), Some("second".to_string()));
+        assert!(coach.evaluate(&chunks, 80.0).is_none());
+        assert_eq!(coach.evaluate(&chunks, 90.0).map(|n| n.rule_id), Some("first".to_string()));
+    }
+
+    #[test]
+    fn rules_need_an_id_message_and_bound() {
+        assert!(rule("ok", CoachingMetric::WordsPerMinute, Some(180.0), None).check().is_ok());
+        assert!(rule("", CoachingMetric::WordsPerMinute, Some(180.0), None).check().is_err());
+        assert!(rule("none", CoachingMetric::WordsPerMinute, None, None).check().is_err());
+        let no_window = CoachingRule { window_secs: Some(0), ..rule("w", CoachingMetric::TalkShare, Some(0.5), None) };
+        assert!(no_window.check().is_err());
+    }
+}
@@ -0,0 +1,461 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use ts_rs::TS;
+
+use crate::coaching::CoachingRule;
+use crate::diarization::DiarizationMode;
+use crate::topics::Topic;
+
+/// `start_capture` config. Required fields default to empty so that
+/// `validate()` can report every missing one at once instead of serde
+/// stopping at the first.
+#[derive(Clone, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CaptureConfig {
+    /// Clerk JWT for authenticating with the STT proxy
+    #[serde(default)]
+    pub auth_token: String,
+    /// STT proxy endpoints (Cloudflare Workers) in order of preference
+    #[serde(default)]
+    #[ts(as = "Option<Vec<String>>", optional)]
+    pub stt_endpoints: Vec<String>,
+    /// Single STT proxy URL, used when `sttEndpoints` is empty
+    #[serde(default)]
+    #[ts(optional)]
+    pub stt_proxy_url: Option<String>,
+    /// STT provider: deepgram | assemblyai | revai
+    #[serde(default = "default_provider")]
+    #[ts(as = "Option<String>", optional)]
+    pub provider: String,
+    /// STT model (e.g., nova-3 for Deepgram)
+    #[serde(default = "default_model")]
+    #[ts(as = "Option<String>", optional)]
+    pub model: String,
+    /// Transcription language (BCP-47, e.g. `es-419`), or `auto` to detect it
+    /// per segment (Deepgram only); changeable mid-session with `set_capture_language`
+    #[serde(default = "default_language")]
+    #[ts(as = "Option<String>", optional)]
+    pub language: String,
+    /// Extra provider options passed through to the STT proxy as query
+    /// parameters, e.g. `{ "diarize": true, "utterance_end_ms": 1000 }`
+    #[serde(default)]
+    #[ts(as = "Option<BTreeMap<String, SttOptionValue>>", optional)]
+    pub stt_options: BTreeMap<String, SttOptionValue>,
+    /// Where speaker ids come from: `auto` (provider, on-device clustering
+    /// when it gives none), `local` or `provider`
+    #[serde(default)]
+    #[ts(as = "Option<DiarizationMode>", optional)]
+    pub diarization: DiarizationMode,
+    /// Thresholds that emit `coaching-nudge`, in order of priority
+    #[serde(default)]
+    #[ts(as = "Option<Vec<CoachingRule>>", optional)]
+    pub coaching_rules: Vec<CoachingRule>,
+    /// Topics or skills to track mentions of, e.g. from the job position
+    #[serde(default)]
+    #[ts(as = "Option<Vec<Topic>>", optional)]
+    pub topics: Vec<Topic>,
+    /// Supabase project URL
+    #[serde(default)]
+    pub supabase_url: String,
+    /// Supabase publishable key
+    #[serde(default)]
+    pub supabase_anon_key: String,
+    /// Internal API key for Edge Functions
+    #[serde(default)]
+    pub internal_api_key: String,
+    /// `http://`, `socks5://` or `socks5h://` proxy for all outbound traffic;
+    /// `direct` ignores the environment. Unset: HTTPS_PROXY / ALL_PROXY / OS settings.
+    #[serde(default)]
+    #[ts(optional)]
+    pub proxy_url: Option<String>,
+    /// Extra trusted CA certificates (PEM contents or file paths) for TLS-inspecting proxies
+    #[serde(default)]
+    #[ts(as = "Option<Vec<String>>", optional)]
+    pub ca_certificates: Vec<String>,
+    /// Connection attempts to the STT proxy before giving up
+    #[serde(default = "default_connect_attempts")]
+    #[ts(as = "Option<u32>", optional)]
+    pub connect_attempts: u32,
+    /// Timeout of a single connection attempt
+    #[serde(default = "default_connect_timeout_secs")]
+    #[ts(as = "Option<u32>", optional)]
+    pub connect_timeout_secs: u32,
+    /// Delay before the second attempt; doubles on each further one (with jitter)
+    #[serde(default = "default_connect_backoff_ms")]
+    #[ts(as = "Option<u32>", optional)]
+    pub connect_backoff_ms: u32,
+    /// KeepAlive is sent after this long without audio (muted, paused, VAD)
+    #[serde(default = "default_keepalive_interval_secs")]
+    #[ts(as = "Option<u32>", optional)]
+    pub keepalive_interval_secs: u32,
+    /// Reconnect when the proxy sends nothing for this long while audio is flowing
+    #[serde(default = "default_stall_timeout_secs")]
+    #[ts(as = "Option<u32>", optional)]
+    pub stall_timeout_secs: u32,
+    /// Settings newer than this build — accepted and logged, never rejected
+    #[serde(flatten)]
+    #[ts(skip)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(untagged)]
+#[ts(export)]
+pub enum SttOptionValue {
+    Bool(bool),
+    Number(f64),
+    Text(String),
+}
+
+impl std::fmt::Display for SttOptionValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SttOptionValue::Bool(b) => write!(f, "{}", b),
+            SttOptionValue::Number(n) => write!(f, "{}", n),
+            SttOptionValue::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// `language` value that asks the provider to detect the language
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// What `provider` calls language detection in its `language` parameter;
+/// None if it cannot detect the language of a stream
+fn detection_language(provider: &str) -> Option<&'static str> {
+    match provider {
+        "deepgram" => Some("multi"),
+        _ => None,
+    }
+}
+
+/// `language` query value sent to the STT proxy for `provider`
+pub fn stream_language<'a>(provider: &str, language: &'a str) -> Result<&'a str, String> {
+    if language != AUTO_LANGUAGE {
+        return Ok(language);
+    }
+    detection_language(provider).ok_or_else(|| format!("\"auto\" is not supported by provider {}", provider))
+}
+
+/// `auto` (if `provider` can detect the language), or a language tag: letters
+/// first, then `-`-separated alphanumeric subtags
+pub fn check_language(value: &str, provider: &str) -> Result<(), String> {
+    if value == AUTO_LANGUAGE {
+        return stream_language(provider, value).map(|_| ());
+    }
+    let mut parts = value.split('-');
+    let primary = parts.next().unwrap_or_default();
+    let valid = (2..=8).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && parts.all(|p| (1..=8).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("{:?} is not a language tag or \"auto\"", value))
+    }
+}
+
+/// Query parameters the app sets itself; `sttOptions` cannot override them
+pub const RESERVED_STT_PARAMS: &[&str] =
+    &["provider", "model", "language", "channels", "sample_rate", "encoding", "protocol"];
+
+fn default_provider() -> String {
+    "deepgram".to_string()
+}
+
+fn default_model() -> String {
+    "nova-3".to_string()
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+fn default_connect_attempts() -> u32 {
+    5
+}
+
+fn default_connect_timeout_secs() -> u32 {
+    10
+}
+
+fn default_connect_backoff_ms() -> u32 {
+    500
+}
+
+fn default_keepalive_interval_secs() -> u32 {
+    5
+}
+
+fn default_stall_timeout_secs() -> u32 {
+    20
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigIssue {
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// Every problem found in a `CaptureConfig`
+#[derive(Debug)]
+pub struct ConfigError(pub Vec<ConfigIssue>);
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid capture config: ")?;
+        for (i, issue) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{} {}", issue.field, issue.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl CaptureConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut issues = Vec::new();
+        let mut require = |field: &'static str, value: &str| {
+            if value.trim().is_empty() {
+                issues.push(ConfigIssue { field, message: "is required".to_string() });
+            }
+        };
+        require("authToken", &self.auth_token);
+        require("supabaseUrl", &self.supabase_url);
+        require("supabaseAnonKey", &self.supabase_anon_key);
+        require("internalApiKey", &self.internal_api_key);
+        require("provider", &self.provider);
+        require("model", &self.model);
+        require("language", &self.language);
+        if !self.language.trim().is_empty() {
+            if let Err(message) = check_language(&self.language, &self.provider) {
+                issues.push(ConfigIssue { field: "language", message });
+            }
+        }
+
+        if !self.supabase_url.trim().is_empty() {
+            if let Err(message) = check_url(&self.supabase_url, &["https", "http"]) {
+                issues.push(ConfigIssue { field: "supabaseUrl", message });
+            }
+        }
+        let endpoints = self.endpoints();
+        if endpoints.is_empty() {
+            issues.push(ConfigIssue { field: "sttEndpoints", message: "is required".to_string() });
+        }
+        for (i, endpoint) in endpoints.iter().enumerate() {
+            if let Err(message) = check_url(endpoint, &["https", "http", "wss", "ws"]) {
+                let field = if self.stt_endpoints.is_empty() { "sttProxyUrl" } else { "sttEndpoints" };
+                issues.push(ConfigIssue { field, message: format!("entry {} {}", i + 1, message) });
+            }
+        }
+
+        for key in self.stt_options.keys() {
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                let message = format!("key {:?} must be letters, digits or underscores", key);
+                issues.push(ConfigIssue { field: "sttOptions", message });
+            } else if RESERVED_STT_PARAMS.contains(&key.as_str()) {
+                let message = format!("cannot set {}; it has its own config field", key);
+                issues.push(ConfigIssue { field: "sttOptions", message });
+            }
+        }
+
+        let mut rule_ids = std::collections::HashSet::new();
+        for (i, rule) in self.coaching_rules.iter().enumerate() {
+            if let Err(e) = rule.check() {
+                issues.push(ConfigIssue { field: "coachingRules", message: format!("entry {} {}", i + 1, e) });
+            } else if !rule_ids.insert(rule.id.trim()) {
+                let message = format!("entry {} repeats id {:?}", i + 1, rule.id);
+                issues.push(ConfigIssue { field: "coachingRules", message });
+            }
+        }
+
+        let mut topic_names = std::collections::HashSet::new();
+        for (i, topic) in self.topics.iter().enumerate() {
+            if let Err(e) = topic.check() {
+                issues.push(ConfigIssue { field: "topics", message: format!("entry {} {}", i + 1, e) });
+            } else if !topic_names.insert(topic.name.trim().to_lowercase()) {
+                let message = format!("entry {} repeats {:?}", i + 1, topic.name);
+                issues.push(ConfigIssue { field: "topics", message });
+            }
+        }
+
+        if self.connect_attempts == 0 {
+            issues.push(ConfigIssue { field: "connectAttempts", message: "must be at least 1".to_string() });
+        }
+        if self.connect_timeout_secs == 0 {
+            issues.push(ConfigIssue { field: "connectTimeoutSecs", message: "must be at least 1".to_string() });
+        }
+        if self.keepalive_interval_secs == 0 {
+            issues.push(ConfigIssue { field: "keepaliveIntervalSecs", message: "must be at least 1".to_string() });
+        }
+        if self.stall_timeout_secs <= self.keepalive_interval_secs {
+            issues.push(ConfigIssue {
+                field: "stallTimeoutSecs",
+                message: "must be longer than keepaliveIntervalSecs".to_string(),
+            });
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError(issues))
+        }
+    }
+
+    /// Configured STT proxy endpoints, falling back to the single `sttProxyUrl`
+    pub fn endpoints(&self) -> Vec<String> {
+        let endpoints = if self.stt_endpoints.is_empty() {
+            self.stt_proxy_url.iter().cloned().collect()
+        } else {
+            self.stt_endpoints.clone()
+        };
+        endpoints
+            .into_iter()
+            .map(|e| e.trim().trim_end_matches('/').to_string())
+            .filter(|e| !e.is_empty())
+            .collect()
+    }
+
+    /// `sttOptions` as query parameters
+    pub fn stt_query_options(&self) -> impl Iterator<Item = (&str, String)> {
+        self.stt_options.iter().map(|(key, value)| (key.as_str(), value.to_string()))
+    }
+
+    pub fn analyze_chunk_url(&self) -> String {
+        format!("{}/functions/v1/analyze-chunk", self.supabase_url.trim_end_matches('/'))
+    }
+
+    pub fn connect_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.connect_timeout_secs.into())
+    }
+
+    pub fn keepalive_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.keepalive_interval_secs.into())
+    }
+
+    pub fn stall_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.stall_timeout_secs.into())
+    }
+
+    pub fn log_unknown_fields(&self) {
+        for key in self.extra.keys() {
+            log::warn!("Ignoring unknown capture config field: {}", key);
+        }
+    }
+}
+
+fn check_url(value: &str, schemes: &[&str]) -> Result<(), String> {
+    let url = url::Url::parse(value.trim()).map_err(|e| format!("is not a valid URL ({})", e))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(format!("must use one of: {}", schemes.join(", ")));
+    }
+    if url.host_str().map_or(true, str::is_empty) {
+        return Err("has no host".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_language_maps_to_provider_detection() {
+        assert_eq!(stream_language("deepgram", "auto"), Ok("multi"));
+        assert_eq!(stream_language("assemblyai", "es-419"), Ok("es-419"));
+        assert!(stream_language("assemblyai", "auto").is_err());
+        assert!(check_language("auto", "deepgram").is_ok());
+        assert!(check_language("auto", "revai").is_err());
+    }
+
+    #[test]
+    fn language_tags() {
+        for tag in ["en", "es-419", "zh-Hant-TW", "pt-BR"] {
+            assert!(check_language(tag, "deepgram").is_ok(), "{}", tag);
+        }
+        for tag in ["", "e", "en_US", "en-", "1en", "en-toolongsubtag"] {
+            assert!(check_language(tag, "deepgram").is_err(), "{}", tag);
+        }
+    }
+
+    fn config(json: serde_json::Value) -> CaptureConfig {
+        let mut base = serde_json::json!({
+            "authToken": "jwt",
+            "sttProxyUrl": "https://stt.example.com",
+            "supabaseUrl": "https://project.supabase.co",
+            "supabaseAnonKey": "anon",
+            "internalApiKey": "internal",
+        });
+        base.as_object_mut().unwrap().extend(json.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    fn fields(config: &CaptureConfig) -> Vec<&'static str> {
+        config.validate().err().map_or(Vec::new(), |e| e.0.iter().map(|i| i.field).collect())
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        let config = config(serde_json::json!({}));
+        assert!(config.validate().is_ok());
+        assert_eq!((config.provider.as_str(), config.model.as_str()), ("deepgram", "nova-3"));
+        assert_eq!(config.language, "en");
+        assert_eq!(config.endpoints(), ["https://stt.example.com"]);
+    }
+
+    #[test]
+    fn every_missing_field_is_reported() {
+        let empty: CaptureConfig = serde_json::from_value(serde_json::json!({})).unwrap();
+        let err = empty.validate().unwrap_err();
+        let fields: Vec<&str> = err.0.iter().map(|i| i.field).collect();
+        assert_eq!(fields, ["authToken", "supabaseUrl", "supabaseAnonKey", "internalApiKey", "sttEndpoints"]);
+        assert!(err.to_string().starts_with("Invalid capture config: authToken is required; "));
+    }
+
+    #[test]
+    fn urls_and_endpoints() {
+        assert_eq!(fields(&config(serde_json::json!({ "supabaseUrl": "ftp://x" }))), ["supabaseUrl"]);
+        assert_eq!(fields(&config(serde_json::json!({ "sttProxyUrl": "wss://" }))), ["sttProxyUrl"]);
+        let listed = config(serde_json::json!({ "sttEndpoints": ["wss://a.example.com/", " ", "b"] }));
+        assert_eq!(listed.endpoints(), ["wss://a.example.com", "b"]);
+        let err = listed.validate().unwrap_err();
+        assert_eq!((err.0.len(), err.0[0].field), (1, "sttEndpoints"));
+        assert!(err.0[0].message.starts_with("entry 2 "));
+    }
+
+    #[test]
+    fn stt_options_cannot_override_own_fields() {
+        let ok = config(serde_json::json!({ "sttOptions": { "diarize": true, "utterance_end_ms": 1000 } }));
+        assert!(ok.validate().is_ok());
+        let options: Vec<(&str, String)> = ok.stt_query_options().collect();
+        assert_eq!(options, [("diarize", "true".to_string()), ("utterance_end_ms", "1000".to_string())]);
+        let bad = config(serde_json::json!({ "sttOptions": { "language": "fr", "bad key": 1 } }));
+        assert_eq!(fields(&bad), ["sttOptions", "sttOptions"]);
+    }
+
+    #[test]
+    fn timings_rules_and_topics() {
+        let zero = config(serde_json::json!({ "connectAttempts": 0, "connectTimeoutSecs": 0 }));
+        assert_eq!(fields(&zero), ["connectAttempts", "connectTimeoutSecs"]);
+        let stall = config(serde_json::json!({ "keepaliveIntervalSecs": 20, "stallTimeoutSecs": 20 }));
+        assert_eq!(fields(&stall), ["stallTimeoutSecs"]);
+
+        let rule = serde_json::json!({ "id": "share", "metric": "talkShare", "above": 0.5, "message": "Listen" });
+        assert_eq!(fields(&config(serde_json::json!({ "coachingRules": [rule, rule] }))), ["coachingRules"]);
+        let topics = serde_json::json!({ "topics": [{ "name": "Rust" }, { "name": " rust " }, { "name": "" }] });
+        assert_eq!(fields(&config(topics)), ["topics", "topics"]);
+        assert_eq!(fields(&config(serde_json::json!({ "provider": "revai", "language": "auto" }))), ["language"]);
+    }
+
+    #[test]
+    fn unknown_fields_are_kept_not_rejected() {
+        let config = config(serde_json::json!({ "futureSetting": 1 }));
+        assert!(config.validate().is_ok());
+        assert!(config.extra.contains_key("futureSetting"));
+    }
+}
@@ -0,0 +1,106 @@
+use base64::Engine;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::sync::watch;
+
+/// Ask the HUD for a fresh Clerk JWT this long before the current one expires
+pub const REFRESH_LEAD_SECS: i64 = 60;
+
+/// STT proxy auth token of a running session. The WebSocket task reads it on
+/// every (re)connect; `update_capture_credentials` replaces it.
+pub struct Credentials {
+    token: watch::Sender<String>,
+    expires_at: Mutex<Option<i64>>,
+    /// Set once `credentials-needed` was emitted for the current token
+    refresh_requested: AtomicBool,
+}
+
+impl Credentials {
+    pub fn new(token: &str) -> Self {
+        Self {
+            token: watch::Sender::new(token.to_string()),
+            expires_at: Mutex::new(token_expiry(token)),
+            refresh_requested: AtomicBool::new(false),
+        }
+    }
+
+    pub fn token(&self) -> String {
+        self.token.borrow().clone()
+    }
+
+    pub fn expires_at(&self) -> Option<i64> {
+        *self.expires_at.lock().unwrap()
+    }
+
+    pub fn update(&self, token: &str) {
+        *self.expires_at.lock().unwrap() = token_expiry(token);
+        self.refresh_requested.store(false, Ordering::Release);
+        self.token.send_replace(token.to_string());
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<String> {
+        self.token.subscribe()
+    }
+
+    /// True the first time this is called within `REFRESH_LEAD_SECS` of expiry
+    pub fn should_request_refresh(&self, now: i64) -> bool {
+        match self.expires_at() {
+            Some(exp) if exp - now <= REFRESH_LEAD_SECS => {
+                !self.refresh_requested.swap(true, Ordering::AcqRel)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// `exp` claim of a JWT (unix seconds). The signature is not checked — the
+/// proxy does that; this only tells us when to ask for a new one.
+pub fn token_expiry(token: &str) -> Option<i64> {
+    let payload = token.split('.').nth(1)?;
+    let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&bytes).ok()?;
+    claims["exp"].as_i64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: &str) -> String {
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(claims);
+        format!("eyJhbGciOiJSUzI1NiJ9.{}.signature", payload)
+    }
+
+    #[test]
+    fn expiry_from_the_exp_claim() {
+        assert_eq!(token_expiry(&jwt(r#"{"sub":"user_1","exp":1760000000}"#)), Some(1760000000));
+        // URL-safe alphabet, and padding some issuers keep
+        let claims = r#"{"name":"Zoë ~?>","exp":42}"#;
+        let padded = base64::engine::general_purpose::URL_SAFE.encode(claims);
+        assert!(padded.ends_with('='));
+        assert_eq!(token_expiry(&format!("h.{}.s", padded)), Some(42));
+
+        assert_eq!(token_expiry(&jwt(r#"{"sub":"user_1"}"#)), None);
+        assert_eq!(token_expiry(&jwt(r#"{"exp":"soon"}"#)), None);
+        assert_eq!(token_expiry("opaque-api-key"), None);
+        assert_eq!(token_expiry("a.!!!.c"), None);
+    }
+
+    #[test]
+    fn refresh_is_requested_once_per_token() {
+        let credentials = Credentials::new(&jwt(r#"{"exp":1000}"#));
+        assert!(!credentials.should_request_refresh(1000 - REFRESH_LEAD_SECS - 1));
+        assert!(credentials.should_request_refresh(1000 - REFRESH_LEAD_SECS));
+        assert!(!credentials.should_request_refresh(990));
+
+        credentials.update(&jwt(r#"{"exp":2000}"#));
+        assert_eq!(credentials.expires_at(), Some(2000));
+        assert!(!credentials.should_request_refresh(990));
+        assert!(credentials.should_request_refresh(1990));
+
+        let opaque = Credentials::new("opaque-api-key");
+        assert!(!opaque.should_request_refresh(i64::MAX));
+    }
+}
@@ -0,0 +1,436 @@
+//! On-device speaker diarization for providers that return no speaker labels.
+//!
+//! The audio of each final segment becomes an embedding — mean and deviation
+//! of MFCCs over its voiced frames. Segments join the closest known speaker if
+//! their means differ by less than `SAME_SPEAKER_DISTANCE` deviations, else
+//! open a new one. Audio is indexed by stream time of the current connection, the
+//! clock the provider's timestamps use, and restarts on every reconnect;
+//! clusters live for the whole session so ids stay consistent across them.
+
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+use ts_rs::TS;
+
+use crate::speakers::SpeakerRole;
+use crate::voiceprint::Voiceprint;
+
+const SAMPLE_RATE: f64 = 16000.0;
+/// 25 ms frames every 10 ms
+const FRAME_LEN: usize = 400;
+const FRAME_HOP: usize = 160;
+const FFT_LEN: usize = 512;
+const MEL_BANDS: usize = 26;
+/// c1..c12 — c0 is loudness, which says more about the mic than the speaker
+const MFCC_COEFFS: usize = 12;
+/// Length of an `embed` result: mean and deviation of each coefficient
+pub const EMBEDDING_LEN: usize = 2 * MFCC_COEFFS;
+/// PCM16 RMS below which a frame is left out of the embedding
+const VOICED_FRAME_RMS: f64 = 300.0;
+/// Segments with less speech than this are too short to tell speakers apart
+const MIN_SEGMENT_VOICED_SECS: f64 = 0.8;
+/// Mean MFCC difference, in pooled standard deviations, below which a segment
+/// joins an existing speaker
+const SAME_SPEAKER_DISTANCE: f64 = 0.15;
+/// Beyond this the closest speaker is used instead of opening a new one
+const MAX_SPEAKERS: usize = 8;
+/// Stream audio kept for segments that have not been finalized yet
+const STREAM_RETENTION_SECS: f64 = 120.0;
+/// Only the end of longer segments is analysed, which keeps `identify` cheap
+/// enough for the reader task
+const MAX_EMBED_SECS: f64 = 8.0;
+
+/// `diarization` capture setting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum DiarizationMode {
+    /// Local clustering for segments the provider did not label
+    #[default]
+    Auto,
+    /// Local clustering for every segment — provider ids restart on each
+    /// reconnect and failover, local ones do not
+    Local,
+    /// Provider labels only
+    Provider,
+}
+
+/// Local speaker of a segment
+#[derive(Debug, Clone, Copy)]
+pub struct SpeakerGuess {
+    pub speaker: usize,
+    pub confidence: f64,
+}
+
+/// Role of a segment from the interviewer voiceprint
+#[derive(Debug, Clone, Copy)]
+pub struct RoleGuess {
+    pub role: SpeakerRole,
+    pub confidence: f64,
+}
+
+/// What the audio of a segment says about who spoke
+#[derive(Debug, Default)]
+pub struct SegmentVoice {
+    pub speaker: Option<SpeakerGuess>,
+    pub role: Option<RoleGuess>,
+}
+
+pub struct Diarizer {
+    mode: DiarizationMode,
+    voiceprint: Option<Voiceprint>,
+    state: Mutex<DiarizerState>,
+}
+
+#[derive(Default)]
+struct DiarizerState {
+    /// Audio sent on the current connection, oldest first
+    stream: VecDeque<i16>,
+    /// Stream samples already dropped from the front of `stream`
+    dropped: usize,
+    speakers: Vec<Speaker>,
+}
+
+struct Speaker {
+    centroid: Vec<f64>,
+    segments: u32,
+}
+
+impl Diarizer {
+    pub fn new(mode: DiarizationMode, voiceprint: Option<Voiceprint>) -> Self {
+        Self { mode, voiceprint, state: Mutex::new(DiarizerState::default()) }
+    }
+
+    fn needs_audio(&self) -> bool {
+        self.mode != DiarizationMode::Provider || self.voiceprint.is_some()
+    }
+
+    /// A new connection starts its stream clock at zero
+    pub fn start_stream(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.stream.clear();
+        state.dropped = 0;
+    }
+
+    /// PCM16 little-endian audio as sent to the proxy
+    pub fn push_stream(&self, audio: &[u8]) {
+        if !self.needs_audio() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.stream.extend(audio.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]])));
+        let max = (STREAM_RETENTION_SECS * SAMPLE_RATE) as usize;
+        if state.stream.len() > max {
+            let excess = state.stream.len() - max;
+            state.stream.drain(..excess);
+            state.dropped += excess;
+        }
+    }
+
+    /// Local speaker (unless the provider's label is used) and voiceprint role
+    /// of the audio between `start` and `end` (stream seconds). Empty if that
+    /// audio is gone or holds too little speech.
+    pub fn identify(&self, start: f64, end: f64, provider_labeled: bool) -> SegmentVoice {
+        let cluster = match self.mode {
+            DiarizationMode::Auto => !provider_labeled,
+            DiarizationMode::Local => true,
+            DiarizationMode::Provider => false,
+        };
+        if !cluster && self.voiceprint.is_none() {
+            return SegmentVoice::default();
+        }
+        // Copy the audio out so `push_stream` is not held up while it is analysed
+        let samples = self.state.lock().unwrap().window(start.max(end - MAX_EMBED_SECS), end);
+        let Some(embedding) = samples.and_then(|s| embed(&s, MIN_SEGMENT_VOICED_SECS)) else {
+            return SegmentVoice::default();
+        };
+        let mut state = self.state.lock().unwrap();
+        let speaker = cluster.then(|| state.cluster(&embedding));
+        // A speaker's centroid is steadier than a single segment
+        let role = self.voiceprint.as_ref().map(|voiceprint| {
+            let voice = speaker.map_or(&embedding, |s| &state.speakers[s.speaker].centroid);
+            voiceprint.role_of(voice)
+        });
+        SegmentVoice { speaker, role }
+    }
+}
+
+impl DiarizerState {
+    fn window(&self, start: f64, end: f64) -> Option<Vec<i16>> {
+        let from = ((start.max(0.0) * SAMPLE_RATE) as usize).checked_sub(self.dropped)?;
+        let to = ((end * SAMPLE_RATE) as usize).saturating_sub(self.dropped).min(self.stream.len());
+        (to > from).then(|| self.stream.range(from..to).copied().collect())
+    }
+
+    fn cluster(&mut self, embedding: &[f64]) -> SpeakerGuess {
+        let closest = self
+            .speakers
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i, voice_distance(&s.centroid, embedding)))
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+        let confidence = |distance| match_confidence(distance, SAME_SPEAKER_DISTANCE);
+
+        match closest {
+            Some((i, distance)) if distance <= SAME_SPEAKER_DISTANCE || self.speakers.len() >= MAX_SPEAKERS => {
+                let speaker = &mut self.speakers[i];
+                speaker.segments += 1;
+                let weight = 1.0 / f64::from(speaker.segments);
+                for (c, e) in speaker.centroid.iter_mut().zip(embedding) {
+                    *c += (e - *c) * weight;
+                }
+                SpeakerGuess { speaker: i, confidence: confidence(distance) }
+            }
+            _ => {
+                self.speakers.push(Speaker { centroid: embedding.to_vec(), segments: 1 });
+                let confidence = closest.map_or(1.0, |(_, distance)| confidence(distance));
+                SpeakerGuess { speaker: self.speakers.len() - 1, confidence }
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Embedding
+// ============================================================================
+
+/// 0.5 at `threshold`, rising to 1 the clearer the match or mismatch
+pub fn match_confidence(distance: f64, threshold: f64) -> f64 {
+    (0.5 + (distance - threshold).abs() / (2.0 * threshold)).min(1.0)
+}
+
+/// Liftered MFCC mean and standard deviation over the voiced frames; None
+/// with less than `min_voiced_secs` of speech
+pub fn embed(samples: &[i16], min_voiced_secs: f64) -> Option<Vec<f64>> {
+    static MEL: OnceLock<Vec<Vec<(usize, f64)>>> = OnceLock::new();
+    static WINDOW: OnceLock<Vec<f64>> = OnceLock::new();
+    let mel = MEL.get_or_init(mel_filterbank);
+    let window = WINDOW.get_or_init(|| {
+        (0..FRAME_LEN)
+            .map(|n| 0.54 - 0.46 * (2.0 * std::f64::consts::PI * n as f64 / (FRAME_LEN - 1) as f64).cos())
+            .collect()
+    });
+
+    let mut frames = Vec::new();
+    for start in (0..samples.len().saturating_sub(FRAME_LEN)).step_by(FRAME_HOP) {
+        let frame = &samples[start..start + FRAME_LEN];
+        let energy = frame.iter().map(|&s| f64::from(s) * f64::from(s)).sum::<f64>() / FRAME_LEN as f64;
+        if energy.sqrt() < VOICED_FRAME_RMS {
+            continue;
+        }
+        frames.push(mfcc(frame, window, mel));
+    }
+    if (frames.len() as f64) < min_voiced_secs * SAMPLE_RATE / FRAME_HOP as f64 {
+        return None;
+    }
+
+    let count = frames.len() as f64;
+    let mean: Vec<f64> = (0..MFCC_COEFFS).map(|k| frames.iter().map(|f| f[k]).sum::<f64>() / count).collect();
+    let deviation = (0..MFCC_COEFFS).map(|k| {
+        (frames.iter().map(|f| (f[k] - mean[k]).powi(2)).sum::<f64>() / count).sqrt()
+    });
+    Some(mean.iter().copied().chain(deviation).collect())
+}
+
+fn mfcc(frame: &[i16], window: &[f64], mel: &[Vec<(usize, f64)>]) -> Vec<f64> {
+    let mut spectrum = vec![(0.0, 0.0); FFT_LEN];
+    // Pre-emphasis boosts the higher formants that carry most speaker identity
+    let mut previous = 0.0;
+    for (i, &s) in frame.iter().enumerate() {
+        let s = f64::from(s) / 32768.0;
+        spectrum[i].0 = (s - 0.97 * previous) * window[i];
+        previous = s;
+    }
+    fft(&mut spectrum);
+    let power: Vec<f64> = spectrum[..=FFT_LEN / 2].iter().map(|(re, im)| re * re + im * im).collect();
+
+    let log_mel: Vec<f64> = mel
+        .iter()
+        .map(|band| band.iter().map(|&(bin, w)| power[bin] * w).sum::<f64>().max(1e-10).ln())
+        .collect();
+
+    // DCT-II, c1..c12, with sinusoidal liftering so no coefficient dominates
+    let lifter = 22.0;
+    (1..=MFCC_COEFFS)
+        .map(|k| {
+            let c: f64 = log_mel
+                .iter()
+                .enumerate()
+                .map(|(m, v)| v * (std::f64::consts::PI * k as f64 * (m as f64 + 0.5) / MEL_BANDS as f64).cos())
+                .sum();
+            c * (1.0 + lifter / 2.0 * (std::f64::consts::PI * k as f64 / lifter).sin())
+        })
+        .collect()
+}
+
+/// Triangular filters between 20 Hz and Nyquist, as (bin, weight) pairs
+fn mel_filterbank() -> Vec<Vec<(usize, f64)>> {
+    let to_mel = |hz: f64| 2595.0 * (1.0 + hz / 700.0).log10();
+    let to_hz = |mel: f64| 700.0 * (10f64.powf(mel / 2595.0) - 1.0);
+    let (low, high) = (to_mel(20.0), to_mel(SAMPLE_RATE / 2.0));
+    let bin_of = |hz: f64| hz * FFT_LEN as f64 / SAMPLE_RATE;
+    let edges: Vec<f64> = (0..MEL_BANDS + 2)
+        .map(|i| bin_of(to_hz(low + (high - low) * i as f64 / (MEL_BANDS + 1) as f64)))
+        .collect();
+
+    (0..MEL_BANDS)
+        .map(|m| {
+            let (left, center, right) = (edges[m], edges[m + 1], edges[m + 2]);
+            (left.ceil() as usize..=(right.floor() as usize).min(FFT_LEN / 2))
+                .filter_map(|bin| {
+                    let x = bin as f64;
+                    let w = if x <= center { (x - left) / (center - left) } else { (right - x) / (right - center) };
+                    (w > 0.0).then_some((bin, w))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// In-place radix-2 FFT over (re, im) pairs; the length must be a power of two
+fn fft(data: &mut [(f64, f64)]) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f64::consts::PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (wr, wi) = ((angle * k as f64).cos(), (angle * k as f64).sin());
+                let (ar, ai) = data[start + k];
+                let (br, bi) = data[start + k + len / 2];
+                let (tr, ti) = (br * wr - bi * wi, br * wi + bi * wr);
+                data[start + k] = (ar + tr, ai + ti);
+                data[start + k + len / 2] = (ar - tr, ai - ti);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+/// RMS over coefficients of the mean difference scaled by the pooled
+/// deviation, so coefficients that vary a lot within speech weigh less
+pub fn voice_distance(a: &[f64], b: &[f64]) -> f64 {
+    let (mean_a, deviation_a) = a.split_at(MFCC_COEFFS);
+    let (mean_b, deviation_b) = b.split_at(MFCC_COEFFS);
+    let sum: f64 = (0..MFCC_COEFFS)
+        .map(|k| {
+            let pooled = deviation_a[k].powi(2) + deviation_b[k].powi(2);
+            if pooled == 0.0 {
+                0.0
+            } else {
+                (mean_a[k] - mean_b[k]).powi(2) / pooled
+            }
+        })
+        .sum();
+    (sum / MFCC_COEFFS as f64).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `secs` of a voice with pitch `f0`, harmonics falling off by `tilt` and a
+    /// 5 Hz vibrato, over a little noise
+    fn voice(f0: f64, tilt: f64, secs: f64, seed: u64) -> Vec<i16> {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let mut phase = 0.0;
+        (0..(secs * SAMPLE_RATE) as usize)
+            .map(|n| {
+                let t = n as f64 / SAMPLE_RATE;
+                phase += 2.0 * std::f64::consts::PI * f0 * (1.0 + 0.03 * (2.0 * std::f64::consts::PI * 5.0 * t).sin())
+                    / SAMPLE_RATE;
+                let harmonics: f64 = (1..=20)
+                    .filter(|&k| f0 * f64::from(k) < SAMPLE_RATE / 2.0)
+                    .map(|k| (phase * f64::from(k)).sin() / f64::from(k).powf(tilt))
+                    .sum();
+                (harmonics * 6000.0 + (rng.f64() - 0.5) * 400.0) as i16
+            })
+            .collect()
+    }
+
+    fn sine(hz: f64, secs: f64) -> Vec<i16> {
+        (0..(secs * SAMPLE_RATE) as usize)
+            .map(|n| ((2.0 * std::f64::consts::PI * hz * n as f64 / SAMPLE_RATE).sin() * 8000.0) as i16)
+            .collect()
+    }
+
+    #[test]
+    fn fft_finds_the_tone() {
+        let mut data: Vec<(f64, f64)> =
+            (0..FFT_LEN).map(|n| ((2.0 * std::f64::consts::PI * 32.0 * n as f64 / FFT_LEN as f64).cos(), 0.0)).collect();
+        fft(&mut data);
+        let peak = (0..FFT_LEN / 2).max_by(|&a, &b| data[a].0.hypot(data[a].1).total_cmp(&data[b].0.hypot(data[b].1)));
+        assert_eq!(peak, Some(32));
+    }
+
+    #[test]
+    fn sine_embeds_to_a_steady_spectrum() {
+        let embedding = embed(&sine(440.0, 2.0), 1.0).expect("2 s of tone is enough");
+        assert_eq!(embedding.len(), EMBEDDING_LEN);
+        assert!(embedding.iter().all(|v| v.is_finite()));
+        let (mean, deviation) = embedding.split_at(MFCC_COEFFS);
+        // A steady tone barely changes from frame to frame
+        let spread = deviation.iter().sum::<f64>() / MFCC_COEFFS as f64;
+        let level = mean.iter().map(|m| m.abs()).sum::<f64>() / MFCC_COEFFS as f64;
+        assert!(spread < level / 10.0, "spread {} level {}", spread, level);
+
+        // A different tone has a different spectrum
+        let other = embed(&sine(1800.0, 2.0), 1.0).unwrap();
+        assert!(other[..MFCC_COEFFS].iter().zip(mean).any(|(a, b)| (a - b).abs() > 1.0));
+    }
+
+    #[test]
+    fn silence_and_short_speech_do_not_embed() {
+        assert!(embed(&vec![0; 32000], 0.5).is_none());
+        assert!(embed(&sine(440.0, 0.4), 0.8).is_none());
+    }
+
+    #[test]
+    fn distance_is_symmetric_and_zero_for_the_same_voice() {
+        let a = embed(&voice(120.0, 1.5, 2.0, 1), 1.0).unwrap();
+        let b = embed(&voice(230.0, 0.6, 2.0, 2), 1.0).unwrap();
+        assert_eq!(voice_distance(&a, &a), 0.0);
+        assert_eq!(voice_distance(&a, &b), voice_distance(&b, &a));
+        assert!(voice_distance(&a, &b) > SAME_SPEAKER_DISTANCE);
+        assert_eq!(match_confidence(SAME_SPEAKER_DISTANCE, SAME_SPEAKER_DISTANCE), 0.5);
+        assert_eq!(match_confidence(0.0, SAME_SPEAKER_DISTANCE), 1.0);
+    }
+
+    #[test]
+    fn two_voices_cluster_apart() {
+        let mut state = DiarizerState::default();
+        let speakers: Vec<usize> = [(120.0, 1.5), (230.0, 0.6), (120.0, 1.5), (230.0, 0.6), (120.0, 1.5)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(f0, tilt))| {
+                let embedding = embed(&voice(f0, tilt, 2.0, i as u64), MIN_SEGMENT_VOICED_SECS).unwrap();
+                state.cluster(&embedding).speaker
+            })
+            .collect();
+        assert_eq!(speakers, [0, 1, 0, 1, 0]);
+    }
+
+    #[test]
+    fn window_follows_the_stream_clock() {
+        let diarizer = Diarizer::new(DiarizationMode::Local, None);
+        let second: Vec<u8> = (0..16000i16).flat_map(|s| s.to_le_bytes()).collect();
+        diarizer.push_stream(&second);
+        diarizer.push_stream(&second);
+        let state = diarizer.state.lock().unwrap();
+        assert_eq!(state.window(0.5, 1.0).map(|w| w.len()), Some(8000));
+        assert_eq!(state.window(1.0, 1.0 + 1.0 / SAMPLE_RATE).map(|w| w[0]), Some(0));
+        assert_eq!(state.window(3.0, 4.0), None);
+    }
+}
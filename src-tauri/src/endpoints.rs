@@ -0,0 +1,176 @@
+use std::time::{Duration, Instant};
+
+/// Timeout of one `/health` probe
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone)]
+struct EndpointHealth {
+    url: String,
+    healthy: bool,
+    latency: Duration,
+}
+
+/// STT proxy endpoints of a session, ordered by health. The first one is used
+/// until it fails; `failover` re-probes the rest and moves to the best of them.
+pub struct EndpointPool {
+    client: reqwest::Client,
+    ranked: Vec<EndpointHealth>,
+}
+
+impl EndpointPool {
+    /// Probe every endpoint and rank them: healthy ones by latency, then the
+    /// unreachable ones in configured order as a last resort
+    pub async fn probe(endpoints: Vec<String>, client: reqwest::Client) -> Self {
+        let ranked = rank(&client, endpoints).await;
+        for e in &ranked {
+            log::info!(
+                "STT endpoint {} — {} ({} ms)",
+                e.url,
+                if e.healthy { "healthy" } else { "unreachable" },
+                e.latency.as_millis()
+            );
+        }
+        Self { client, ranked }
+    }
+
+    pub fn current(&self) -> &str {
+        &self.ranked[0].url
+    }
+
+    pub fn count(&self) -> usize {
+        self.ranked.len()
+    }
+
+    /// Move the current endpoint to the back and switch to the healthiest of
+    /// the others. None when there is nothing to switch to.
+    pub async fn failover(&mut self) -> Option<String> {
+        if self.ranked.len() < 2 {
+            return None;
+        }
+        let failed = self.ranked.remove(0);
+        let others = self.ranked.drain(..).map(|e| e.url).collect();
+        self.ranked = rank(&self.client, others).await;
+        self.ranked.push(EndpointHealth { healthy: false, ..failed });
+        Some(self.current().to_string())
+    }
+}
+
+async fn rank(client: &reqwest::Client, endpoints: Vec<String>) -> Vec<EndpointHealth> {
+    let probes = endpoints.into_iter().map(|url| async move {
+        let started = Instant::now();
+        let healthy = check(client, &url).await;
+        EndpointHealth { url, healthy, latency: started.elapsed() }
+    });
+    let mut ranked = futures_util::future::join_all(probes).await;
+    // Stable sort keeps the configured order among unreachable endpoints
+    ranked.sort_by_key(|e| (!e.healthy, if e.healthy { e.latency } else { Duration::ZERO }));
+    ranked
+}
+
+/// Any answer below 500 means the Worker is up, even if it has no `/health` route
+async fn check(client: &reqwest::Client, endpoint: &str) -> bool {
+    let url = match route(endpoint, "health", true) {
+        Ok(url) => url,
+        Err(e) => {
+            log::warn!("STT endpoint {} {}", endpoint, e);
+            return false;
+        }
+    };
+    match client.get(url).timeout(HEALTH_CHECK_TIMEOUT).send().await {
+        Ok(response) => !response.status().is_server_error(),
+        Err(e) => {
+            log::warn!("STT endpoint {} health check failed: {}", endpoint, e);
+            false
+        }
+    }
+}
+
+/// `/ws` route of an endpoint, with `http(s)` mapped to `ws(s)`
+pub fn stream_url(endpoint: &str) -> Result<url::Url, String> {
+    route(endpoint, "ws", false)
+}
+
+/// `route` appended to the endpoint's path, so proxies mounted under a
+/// prefix (`https://host:8443/stt`) keep it
+fn route(endpoint: &str, route: &str, http: bool) -> Result<url::Url, String> {
+    let mut url = url::Url::parse(endpoint).map_err(|e| format!("is not a valid URL ({})", e))?;
+    let scheme = match (url.scheme(), http) {
+        ("https" | "wss", true) => "https",
+        ("http" | "ws", true) => "http",
+        ("https" | "wss", false) => "wss",
+        ("http" | "ws", false) => "ws",
+        (other, _) => return Err(format!("has unsupported scheme {}", other)),
+    };
+    url.set_scheme(scheme).map_err(|_| format!("cannot use scheme {}", scheme))?;
+    url.path_segments_mut()
+        .map_err(|_| "cannot have a path".to_string())?
+        .pop_if_empty()
+        .push(route);
+    url.set_query(None);
+    url.set_fragment(None);
+    Ok(url)
+}
+
+/// Host of an endpoint, used in `provider-switch` events
+pub fn endpoint_host(endpoint: &str) -> String {
+    url::Url::parse(endpoint)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| endpoint.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn routes_keep_the_prefix_and_map_the_scheme() {
+        assert_eq!(stream_url("https://stt.example.com").unwrap().as_str(), "wss://stt.example.com/ws");
+        assert_eq!(stream_url("http://localhost:8787/").unwrap().as_str(), "ws://localhost:8787/ws");
+        assert_eq!(stream_url("wss://host:8443/stt/?x=1#y").unwrap().as_str(), "wss://host:8443/stt/ws");
+        assert_eq!(route("wss://host/stt", "health", true).unwrap().as_str(), "https://host/stt/health");
+        assert!(stream_url("ftp://host").is_err());
+        assert!(stream_url("not a url").is_err());
+    }
+
+    #[test]
+    fn host_for_events() {
+        assert_eq!(endpoint_host("https://eu.stt.example.com:8443/stt"), "eu.stt.example.com");
+        assert_eq!(endpoint_host("garbage"), "garbage");
+    }
+
+    /// Endpoint that answers every request with `status`
+    async fn serve(status: u16) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+                let response = format!("HTTP/1.1 {} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn healthy_endpoints_first_and_failover_moves_on() {
+        let down = serve(503).await;
+        let missing_route = serve(404).await;
+        let pool = EndpointPool::probe(vec![down.clone(), missing_route.clone()], reqwest::Client::new()).await;
+        assert_eq!(pool.current(), missing_route);
+        assert_eq!(pool.count(), 2);
+
+        let up = serve(200).await;
+        let mut pool = EndpointPool::probe(vec![up.clone(), down.clone()], reqwest::Client::new()).await;
+        assert_eq!(pool.current(), up);
+        assert_eq!(pool.failover().await.as_deref(), Some(down.as_str()));
+        // The failed endpoint waits at the back
+        assert_eq!(pool.failover().await.as_deref(), Some(up.as_str()));
+
+        let mut single = EndpointPool::probe(vec![up], reqwest::Client::new()).await;
+        assert_eq!(single.failover().await, None);
+    }
+}
@@ -0,0 +1,120 @@
+//! Error returned by every command and carried by `capture-error` / `capture-warning`.
+//! `code` is stable and meant for the HUD to switch on; `message` is for display only.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+use crate::config::ConfigError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[ts(export)]
+pub enum ErrorCode {
+    // Commands
+    InvalidConfig,
+    AlreadyRecording,
+    NotRecording,
+    InvalidState,
+    // Audio sources
+    MicPermissionDenied,
+    DeviceNotFound,
+    UnsupportedAudioFormat,
+    AudioDeviceError,
+    SystemAudioUnavailable,
+    // STT proxy
+    SttAuthExpired,
+    SttUnreachable,
+    SttRejected,
+    SttQuotaExceeded,
+    SttProviderError,
+    SttConnectionLost,
+    SttStalled,
+    // analyze-chunk
+    AnalysisRejected,
+    AnalysisUnreachable,
+    // export_transcript
+    NothingToExport,
+    ExportCancelled,
+    // enroll_interviewer_voice
+    NotEnoughSpeech,
+    Io,
+    Internal,
+}
+
+impl ErrorCode {
+    /// Whether the same call can succeed later without the user changing anything
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::AudioDeviceError
+                | ErrorCode::SttUnreachable
+                | ErrorCode::SttProviderError
+                | ErrorCode::SttConnectionLost
+                | ErrorCode::SttStalled
+                | ErrorCode::AnalysisUnreachable
+                | ErrorCode::Io
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CaptureError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub retryable: bool,
+    /// Details for the code, e.g. the invalid fields for INVALID_CONFIG or `status` for HTTP failures
+    pub context: BTreeMap<String, String>,
+}
+
+impl CaptureError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            retryable: code.is_retryable(),
+            context: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_context(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.context.insert(key.into(), value.to_string());
+        self
+    }
+}
+
+impl std::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+impl From<&ConfigError> for CaptureError {
+    fn from(e: &ConfigError) -> Self {
+        e.0.iter().fold(
+            CaptureError::new(ErrorCode::InvalidConfig, e.to_string()),
+            |err, issue| err.with_context(issue.field, &issue.message),
+        )
+    }
+}
+
+/// Keep the code of a `CaptureError` raised anywhere in the chain; anything
+/// unclassified becomes INTERNAL
+impl From<anyhow::Error> for CaptureError {
+    fn from(e: anyhow::Error) -> Self {
+        if let Some(err) = e.downcast_ref::<CaptureError>() {
+            return err.clone();
+        }
+        if let Some(config) = e.downcast_ref::<ConfigError>() {
+            return config.into();
+        }
+        if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            return CaptureError::new(ErrorCode::Io, e.to_string()).with_context("kind", format!("{:?}", io.kind()));
+        }
+        CaptureError::new(ErrorCode::Internal, e.to_string())
+    }
+}
@@ -0,0 +1,96 @@
+//! Payloads of events emitted to the HUD. Event names are listed on each type;
+//! `transcript` carries `TranscriptChunk`, `capture-state` / `capture-stopped`
+//! carry `CaptureStatus` and `capture-error` / `capture-warning` (non-fatal)
+//! carry `error::CaptureError`. `stt-auth-expired` also carries the
+//! `CaptureError` when the proxy rejected the Clerk JWT. `conversation-analytics`
+//! carries `analytics::ConversationAnalytics`, `coaching-nudge`
+//! `coaching::CoachingNudge`, `qa-segment` `qa::QaSegment` and
+//! `topic-covered` `topics::TopicMention`.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::error::CaptureError;
+
+/// `capture-started`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CaptureStarted {
+    pub mic: bool,
+    pub system_audio: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub enum CredentialsReason {
+    /// The token expires within a minute
+    Expiring,
+    /// The STT proxy rejected the token; streaming resumes once a new one arrives
+    AuthFailed,
+}
+
+/// `credentials-needed` — answer with `update_capture_credentials`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CredentialsNeeded {
+    pub session_id: String,
+    pub reason: CredentialsReason,
+    /// `exp` of the current token (unix seconds), if it has one
+    #[ts(type = "number | null")]
+    pub expires_at: Option<i64>,
+}
+
+/// `stt-connecting` — one per connection attempt to the STT proxy
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SttConnecting {
+    pub session_id: String,
+    /// 1-based
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Why the previous attempt failed
+    pub last_error: Option<CaptureError>,
+}
+
+/// `stt-stalled` — the proxy went quiet while audio was flowing; a reconnect follows
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SttStalled {
+    pub session_id: String,
+    /// Time since the last message from the proxy
+    pub silent_secs: f64,
+    pub stall_timeout_secs: u32,
+}
+
+/// `mic-started`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct MicStarted {
+    pub device: String,
+}
+
+/// `system-audio-started` (macOS only)
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SystemAudioStarted {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+/// `provider-switch` — forwarded from the STT proxy's provider failover, and
+/// emitted by the app itself on STT endpoint failover (`from`/`to` are hosts then)
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProviderSwitch {
+    pub from: String,
+    pub to: String,
+    #[serde(default)]
+    pub reason: String,
+}
@@ -0,0 +1,282 @@
+use serde::Deserialize;
+use std::fmt::Write;
+use tauri_plugin_dialog::DialogExt;
+use tokio::sync::oneshot;
+use ts_rs::TS;
+
+use crate::error::{CaptureError, ErrorCode};
+use crate::transcript::{ChunkKind, TranscriptBackfill, TranscriptChunk};
+
+#[derive(Debug, Clone, Copy, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum ExportFormat {
+    Srt,
+    Vtt,
+    Markdown,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Srt => "srt",
+            ExportFormat::Vtt => "vtt",
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Write the current session's transcript to a file the user picks in a
+/// native save dialog, prefilled with `file_name` if given. The webview never
+/// chooses the location itself. Returns the written path.
+pub async fn export_transcript(
+    app: &tauri::AppHandle,
+    transcript: TranscriptBackfill,
+    format: ExportFormat,
+    file_name: Option<String>,
+) -> Result<String, anyhow::Error> {
+    if transcript.chunks.is_empty() {
+        return Err(CaptureError::new(ErrorCode::NothingToExport, "No transcript to export").into());
+    }
+
+    let file_name = match file_name {
+        Some(name) => suggested_file_name(&name, format)?,
+        None => format!(
+            "interview-{}-transcript.{}",
+            transcript.interview_id.unwrap_or_default(),
+            format.extension()
+        ),
+    };
+    let (tx, rx) = oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter(format.extension().to_uppercase(), &[format.extension()])
+        .set_file_name(file_name)
+        .save_file(move |file| {
+            let _ = tx.send(file);
+        });
+    let path = rx
+        .await?
+        .ok_or_else(|| CaptureError::new(ErrorCode::ExportCancelled, "Export cancelled"))?
+        .into_path()?;
+
+    let contents = render(&transcript, format)?;
+    tokio::fs::write(&path, contents).await?;
+    log::info!("Transcript exported ({:?}) to {}", format, path.display());
+    Ok(path.display().to_string())
+}
+
+/// A bare file name with the format's extension; anything path-like is refused
+fn suggested_file_name(name: &str, format: ExportFormat) -> Result<String, CaptureError> {
+    let name = name.trim();
+    let plain = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\', ':', '\0'])
+        && std::path::Path::new(name).file_name().and_then(|n| n.to_str()) == Some(name);
+    if !plain {
+        return Err(CaptureError::new(ErrorCode::InvalidConfig, "fileName must be a plain file name")
+            .with_context("fileName", name));
+    }
+    let extension = format!(".{}", format.extension());
+    Ok(if name.to_lowercase().ends_with(&extension) { name.to_string() } else { format!("{}{}", name, extension) })
+}
+
+pub fn render(transcript: &TranscriptBackfill, format: ExportFormat) -> Result<String, anyhow::Error> {
+    Ok(match format {
+        ExportFormat::Srt => render_srt(&transcript.chunks),
+        ExportFormat::Vtt => render_vtt(&transcript.chunks),
+        ExportFormat::Markdown => render_markdown(transcript),
+        ExportFormat::Json => render_json(transcript)?,
+    })
+}
+
+// ============================================================================
+// Formats
+// ============================================================================
+
+fn render_srt(chunks: &[TranscriptChunk]) -> String {
+    let mut out = String::new();
+    for (i, c) in chunks.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "{}\n{} --> {}\n{}\n",
+            i + 1,
+            format_timestamp(c.start, ','),
+            format_timestamp(cue_end(c), ','),
+            cue_text(c)
+        );
+    }
+    out
+}
+
+fn render_vtt(chunks: &[TranscriptChunk]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for c in chunks {
+        if c.kind != ChunkKind::Speech {
+            let _ = writeln!(
+                out,
+                "{}\n{} --> {}\n{}\n",
+                c.seq,
+                format_timestamp(c.start, '.'),
+                format_timestamp(cue_end(c), '.'),
+                escape_vtt(&c.text)
+            );
+            continue;
+        }
+        // Subtitle formats have no confidence field — carry it as a NOTE block
+        let _ = writeln!(
+            out,
+            "NOTE confidence={:.2}\n\n{}\n{} --> {}\n<v {}>{}\n",
+            c.confidence,
+            c.seq,
+            format_timestamp(c.start, '.'),
+            format_timestamp(cue_end(c), '.'),
+            escape_vtt(&c.speaker),
+            escape_vtt(&c.text)
+        );
+    }
+    out
+}
+
+fn render_markdown(transcript: &TranscriptBackfill) -> String {
+    let mut out = String::from("# Interview Transcript\n\n");
+    if let Some(id) = transcript.interview_id {
+        let _ = writeln!(out, "- **Interview:** {}", id);
+    }
+    let _ = writeln!(out, "- **Exported:** {}\n", chrono::Utc::now().to_rfc3339());
+
+    // One heading per speaker turn, consecutive chunks grouped underneath
+    let mut current_speaker: Option<&str> = None;
+    for c in &transcript.chunks {
+        if c.kind != ChunkKind::Speech {
+            let _ = writeln!(
+                out,
+                "---\n\n_{} ({} – {})_\n",
+                c.text,
+                format_clock(c.start),
+                format_clock(c.end)
+            );
+            current_speaker = None;
+            continue;
+        }
+        if current_speaker != Some(c.speaker.as_str()) {
+            let _ = writeln!(out, "### {} — {}\n", c.speaker, format_clock(c.start));
+            current_speaker = Some(&c.speaker);
+        }
+        let _ = writeln!(
+            out,
+            "`{}` {} _({:.0}%)_\n",
+            format_clock(c.start),
+            c.text,
+            c.confidence * 100.0
+        );
+    }
+    out
+}
+
+fn render_json(transcript: &TranscriptBackfill) -> Result<String, anyhow::Error> {
+    Ok(serde_json::to_string_pretty(&serde_json::json!({
+        "interviewId": transcript.interview_id,
+        "exportedAt": chrono::Utc::now().to_rfc3339(),
+        "chunks": transcript.chunks,
+    }))?)
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Zero-length cues are dropped by most players — give them a minimal span
+fn cue_end(c: &TranscriptChunk) -> f64 {
+    c.end.max(c.start + 0.5)
+}
+
+fn cue_text(c: &TranscriptChunk) -> String {
+    match c.kind {
+        ChunkKind::Speech => format!("{}: {}", c.speaker, c.text),
+        ChunkKind::Pause | ChunkKind::Mute => c.text.clone(),
+    }
+}
+
+/// `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (WebVTT)
+fn format_timestamp(seconds: f64, millis_sep: char) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        millis_sep,
+        total_ms % 1000
+    )
+}
+
+/// `HH:MM:SS` for human-readable output
+fn format_clock(seconds: f64) -> String {
+    let total = seconds.max(0.0) as u64;
+    format!("{:02}:{:02}:{:02}", total / 3600, (total / 60) % 60, total % 60)
+}
+
+fn escape_vtt(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backfill(chunks: Vec<TranscriptChunk>) -> TranscriptBackfill {
+        TranscriptBackfill { interview_id: Some(7), last_seq: chunks.len() as u64, chunks }
+    }
+
+    #[test]
+    fn timestamps_use_the_format_separator() {
+        assert_eq!(format_timestamp(3723.4567, ','), "01:02:03,457");
+        assert_eq!(format_timestamp(-1.0, '.'), "00:00:00.000");
+        assert_eq!(format_clock(59.9), "00:00:59");
+    }
+
+    #[test]
+    fn srt_numbers_cues_and_pads_zero_length_ones() {
+        let srt = render_srt(&[TranscriptChunk::speech(1, "Ana", "Hello", 1.0, 1.0)]);
+        assert_eq!(srt, "1\n00:00:01,000 --> 00:00:01,500\nAna: Hello\n\n");
+    }
+
+    #[test]
+    fn vtt_escapes_markup_and_notes_confidence() {
+        let vtt = render_vtt(&[TranscriptChunk::speech(3, "A<b>", "x < y & z", 0.0, 2.0)]);
+        assert!(vtt.starts_with("WEBVTT\n\n"));
+        assert!(vtt.contains("NOTE confidence=0.90"));
+        assert!(vtt.contains("<v A&lt;b&gt;>x &lt; y &amp; z"));
+    }
+
+    #[test]
+    fn markdown_groups_consecutive_chunks_of_a_speaker() {
+        let md = render_markdown(&backfill(vec![
+            TranscriptChunk::speech(1, "Ana", "One", 0.0, 1.0),
+            TranscriptChunk::speech(2, "Ana", "Two", 1.0, 2.0),
+            TranscriptChunk::speech(3, "Ben", "Three", 2.0, 3.0),
+        ]));
+        assert_eq!(md.matches("### Ana").count(), 1);
+        assert_eq!(md.matches("### Ben").count(), 1);
+    }
+
+    #[test]
+    fn json_keeps_every_chunk() {
+        let json = render_json(&backfill(vec![TranscriptChunk::speech(1, "Ana", "Hi", 0.0, 1.0)])).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["interviewId"], 7);
+        assert_eq!(value["chunks"][0]["text"], "Hi");
+    }
+
+    #[test]
+    fn suggested_names_must_be_plain_file_names() {
+        assert_eq!(suggested_file_name("notes", ExportFormat::Srt).unwrap(), "notes.srt");
+        assert_eq!(suggested_file_name("notes.MD", ExportFormat::Markdown).unwrap(), "notes.MD");
+        for bad in ["", "../notes", "/etc/passwd", "dir/notes", "..\\notes", ".zshrc", "C:notes"] {
+            assert!(suggested_file_name(bad, ExportFormat::Json).is_err(), "{:?} accepted", bad);
+        }
+    }
+}
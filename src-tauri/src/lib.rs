@@ -1,42 +1,175 @@
+mod activity;
+mod analytics;
 mod audio;
+mod coaching;
+mod config;
+mod credentials;
+mod diarization;
+mod endpoints;
+mod error;
+mod events;
+mod export;
+mod network;
+mod protocol;
+mod qa;
+mod session;
+mod speakers;
+mod stats;
+mod topics;
+mod transcript;
+mod voiceprint;
+
+use error::CaptureError;
+use session::CaptureManager;
+use tauri::State;
 
 #[tauri::command]
 async fn start_capture(
     app: tauri::AppHandle,
+    manager: State<'_, CaptureManager>,
     interview_id: i64,
-    config: serde_json::Value,
-) -> Result<String, String> {
-    audio::start_capture(app, interview_id, config)
+    config: config::CaptureConfig,
+) -> Result<String, CaptureError> {
+    manager.start(app, interview_id, config).await
+}
+
+#[tauri::command]
+async fn stop_capture(
+    app: tauri::AppHandle,
+    manager: State<'_, CaptureManager>,
+) -> Result<session::CaptureStatus, CaptureError> {
+    manager.stop(app).await
+}
+
+#[tauri::command]
+fn pause_capture(
+    app: tauri::AppHandle,
+    manager: State<'_, CaptureManager>,
+) -> Result<session::CaptureStatus, CaptureError> {
+    manager.pause(&app)
+}
+
+#[tauri::command]
+fn resume_capture(
+    app: tauri::AppHandle,
+    manager: State<'_, CaptureManager>,
+) -> Result<session::CaptureStatus, CaptureError> {
+    manager.resume(&app)
+}
+
+#[tauri::command]
+fn update_capture_credentials(
+    manager: State<'_, CaptureManager>,
+    auth_token: String,
+) -> Result<session::CaptureStatus, CaptureError> {
+    manager.update_credentials(&auth_token)
+}
+
+#[tauri::command]
+fn set_capture_language(
+    manager: State<'_, CaptureManager>,
+    language: String,
+) -> Result<session::CaptureStatus, CaptureError> {
+    manager.set_language(&language)
+}
+
+#[tauri::command]
+fn set_source_muted(
+    app: tauri::AppHandle,
+    manager: State<'_, CaptureManager>,
+    source: activity::AudioSource,
+    muted: bool,
+) -> Result<session::CaptureStatus, CaptureError> {
+    manager.set_source_muted(&app, source, muted)
+}
+
+#[tauri::command]
+fn set_speaker_map(
+    app: tauri::AppHandle,
+    manager: State<'_, CaptureManager>,
+    map: speakers::SpeakerMap,
+) -> Result<transcript::TranscriptBackfill, CaptureError> {
+    manager.set_speaker_map(&app, map)
+}
+
+#[tauri::command]
+fn get_capture_status(manager: State<'_, CaptureManager>) -> session::CaptureStatus {
+    manager.status()
+}
+
+#[tauri::command]
+fn get_conversation_analytics(manager: State<'_, CaptureManager>) -> Option<analytics::ConversationAnalytics> {
+    manager.analytics()
+}
+
+#[tauri::command]
+fn get_topic_coverage(manager: State<'_, CaptureManager>) -> topics::TopicCoverage {
+    manager.topic_coverage()
+}
+
+#[tauri::command]
+fn get_session_transcript(
+    manager: State<'_, CaptureManager>,
+    since_seq: Option<u64>,
+) -> transcript::TranscriptBackfill {
+    manager.transcript(since_seq.unwrap_or(0))
+}
+
+#[tauri::command]
+async fn export_transcript(
+    app: tauri::AppHandle,
+    manager: State<'_, CaptureManager>,
+    format: export::ExportFormat,
+    file_name: Option<String>,
+) -> Result<String, CaptureError> {
+    export::export_transcript(&app, manager.transcript(0), format, file_name)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(CaptureError::from)
 }
 
 #[tauri::command]
-async fn stop_capture(app: tauri::AppHandle) -> Result<String, String> {
-    audio::stop_capture(app).await.map_err(|e| e.to_string())
+async fn enroll_interviewer_voice(
+    app: tauri::AppHandle,
+    duration_secs: Option<u32>,
+) -> Result<voiceprint::VoiceEnrollment, CaptureError> {
+    voiceprint::enroll(&app, duration_secs).await.map_err(CaptureError::from)
 }
 
 #[tauri::command]
-fn get_capture_status() -> serde_json::Value {
-    audio::get_status()
+async fn clear_interviewer_voice(app: tauri::AppHandle) -> Result<bool, CaptureError> {
+    voiceprint::clear(&app).await.map_err(CaptureError::from)
 }
 
 #[tauri::command]
-fn list_audio_devices() -> Result<Vec<serde_json::Value>, String> {
-    audio::list_devices().map_err(|e| e.to_string())
+fn list_audio_devices() -> Result<Vec<audio::AudioDevice>, CaptureError> {
+    audio::list_devices().map_err(CaptureError::from)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_log::Builder::new().build())
+        .plugin(tauri_plugin_dialog::init())
         // updater disabled until signing is configured
         // .plugin(tauri_plugin_updater::Builder::new().build())
+        .manage(CaptureManager::default())
         .invoke_handler(tauri::generate_handler![
             start_capture,
             stop_capture,
+            pause_capture,
+            resume_capture,
+            update_capture_credentials,
+            set_capture_language,
+            set_source_muted,
+            set_speaker_map,
             get_capture_status,
+            get_session_transcript,
+            get_conversation_analytics,
+            get_topic_coverage,
+            export_transcript,
             list_audio_devices,
+            enroll_interviewer_voice,
+            clear_interviewer_voice,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,195 @@
+//! Messages the STT proxy sends over the WebSocket, tagged by `type`.
+//!
+//! The app announces `PROTOCOL_VERSION` in the connect URL and the proxy
+//! answers with its own in `connected`. Unknown message types and fields are
+//! tolerated so either side can add to the protocol without breaking the other;
+//! only a proxy older than `MIN_PROXY_PROTOCOL_VERSION` is refused.
+
+use serde::Deserialize;
+
+use crate::events::ProviderSwitch;
+
+/// Protocol spoken by this build
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest proxy protocol this build still understands. Proxies that predate
+/// the handshake omit the version and are treated as v1.
+pub const MIN_PROXY_PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProxyMessage {
+    Connected {
+        #[serde(default)]
+        provider: Option<String>,
+        #[serde(default)]
+        protocol_version: Option<u32>,
+    },
+    Transcript(TranscriptMessage),
+    ProviderSwitch(ProviderSwitch),
+    Error {
+        #[serde(default)]
+        message: Option<String>,
+        #[serde(default)]
+        code: Option<String>,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptMessage {
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub is_final: bool,
+    #[serde(default)]
+    pub words: Vec<Word>,
+    /// Offset of the segment in the stream (seconds since the connection opened)
+    #[serde(default)]
+    pub start: Option<f64>,
+    #[serde(default)]
+    pub duration: Option<f64>,
+    #[serde(default)]
+    pub confidence: Option<f64>,
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Language the provider detected, when streaming with `language=auto`
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Word {
+    /// As transcribed, punctuated if the provider punctuates
+    #[serde(default)]
+    pub word: Option<String>,
+    #[serde(default)]
+    pub speaker: Option<i64>,
+    #[serde(default)]
+    pub start: Option<f64>,
+    #[serde(default)]
+    pub end: Option<f64>,
+    /// Per-word language of code-switching models
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// The part of a final segment one speaker said
+#[derive(Debug)]
+pub struct SpeakerTurn {
+    /// Provider speaker; None without diarization
+    pub speaker: Option<i64>,
+    pub text: String,
+    /// Stream offsets (seconds since the connection opened)
+    pub stream_span: Option<(f64, f64)>,
+    /// Spoken length, for turns without offsets
+    pub duration: f64,
+    pub language: Option<String>,
+}
+
+impl TranscriptMessage {
+    /// The segment split where the word speaker changes. A single turn with
+    /// the segment text when there is one speaker or the words carry no text.
+    pub fn turns(&self) -> Vec<SpeakerTurn> {
+        let runs: Vec<&[Word]> = self.words.chunk_by(|a, b| a.speaker == b.speaker).collect();
+        if runs.len() < 2 || self.words.iter().any(|w| w.word.is_none()) {
+            return vec![SpeakerTurn {
+                speaker: self.words.first().and_then(|w| w.speaker),
+                text: self.text.clone(),
+                stream_span: word_span(&self.words)
+                    .or_else(|| self.start.zip(self.duration).map(|(start, duration)| (start, start + duration))),
+                duration: word_span(&self.words).map(|(start, end)| end - start).or(self.duration).unwrap_or(0.0),
+                language: self.language.clone().or_else(|| word_language(&self.words)),
+            }];
+        }
+        runs.into_iter()
+            .map(|words| SpeakerTurn {
+                speaker: words[0].speaker,
+                text: words.iter().filter_map(|w| w.word.as_deref()).collect::<Vec<_>>().join(" "),
+                stream_span: word_span(words),
+                duration: word_span(words).map_or(0.0, |(start, end)| end - start),
+                language: self.language.clone().or_else(|| word_language(words)),
+            })
+            .collect()
+    }
+}
+
+/// Start of the first word to end of the last
+fn word_span(words: &[Word]) -> Option<(f64, f64)> {
+    words.first()?.start.zip(words.last()?.end)
+}
+
+/// Most common word language
+fn word_language(words: &[Word]) -> Option<String> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for language in words.iter().filter_map(|w| w.language.as_deref()) {
+        match counts.iter_mut().find(|(l, _)| *l == language) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((language, 1)),
+        }
+    }
+    // max_by_key keeps the last maximum; rev() makes ties go to the first seen
+    counts.into_iter().rev().max_by_key(|(_, n)| *n).map(|(l, _)| l.to_string())
+}
+
+impl ProxyMessage {
+    pub fn parse(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+}
+
+/// `type` of a message that parsed as `Unknown`, for logging
+pub fn message_type(text: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Tagged {
+        #[serde(rename = "type")]
+        kind: String,
+    }
+    serde_json::from_str::<Tagged>(text).ok().map(|t| t.kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript(json: &str) -> TranscriptMessage {
+        match ProxyMessage::parse(json).unwrap() {
+            ProxyMessage::Transcript(t) => t,
+            other => panic!("not a transcript: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn turns_split_where_the_speaker_changes() {
+        let t = transcript(
+            r#"{"type":"transcript","is_final":true,"text":"so tell me yeah sure","words":[
+                {"word":"So","speaker":0,"start":1.0,"end":1.2},
+                {"word":"tell","speaker":0,"start":1.2,"end":1.4},
+                {"word":"me?","speaker":0,"start":1.4,"end":1.6},
+                {"word":"Yeah,","speaker":1,"start":1.5,"end":1.8,"language":"es"},
+                {"word":"sure.","speaker":1,"start":1.8,"end":2.1}]}"#,
+        );
+        let turns = t.turns();
+        assert_eq!(turns.len(), 2);
+        assert_eq!((turns[0].speaker, turns[0].text.as_str(), turns[0].stream_span), (Some(0), "So tell me?", Some((1.0, 1.6))));
+        assert_eq!((turns[1].speaker, turns[1].text.as_str(), turns[1].stream_span), (Some(1), "Yeah, sure.", Some((1.5, 2.1))));
+        assert_eq!(turns[1].language.as_deref(), Some("es"));
+        assert_eq!(turns[0].language, None);
+    }
+
+    #[test]
+    fn one_turn_without_word_text_or_speaker_change() {
+        let t = transcript(
+            r#"{"type":"transcript","is_final":true,"text":"Hola, ¿qué tal?","start":3.0,"duration":1.5,
+                "words":[{"speaker":0,"start":3.1,"end":3.5},{"speaker":1,"start":3.6,"end":4.2}]}"#,
+        );
+        let turns = t.turns();
+        assert_eq!(turns.len(), 1);
+        assert_eq!((turns[0].text.as_str(), turns[0].stream_span), ("Hola, ¿qué tal?", Some((3.1, 4.2))));
+
+        let t = transcript(r#"{"type":"transcript","is_final":true,"text":"Hi","start":3.0,"duration":1.5}"#);
+        let turns = t.turns();
+        assert_eq!((turns[0].speaker, turns[0].stream_span, turns[0].duration), (None, Some((3.0, 4.5)), 1.5));
+    }
+}
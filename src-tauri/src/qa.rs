@@ -0,0 +1,265 @@
+//! Question/answer segmentation of the transcript: an interviewer question
+//! opens a segment, the other speakers' replies are its answer, and the next
+//! question after an answer closes it.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::speakers::SpeakerRole;
+use crate::transcript::{ChunkKind, TranscriptChunk};
+
+/// Openers that make a sentence a question even when the provider gave no `?`
+const INTERROGATIVE_OPENERS: &[&str] = &[
+    "what", "how", "why", "when", "where", "who", "whom", "whose", "which",
+    "can you", "could you", "would you", "will you", "do you", "did you", "have you", "are you",
+    "is there", "is it", "tell me", "walk me through", "describe", "explain", "talk me through",
+];
+
+/// `qa-segment` — sent when a question opens a segment, as the answer comes
+/// in, and once more when the segment closes
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct QaSegment {
+    /// 1-based per session; also `segmentId` on the transcript chunks
+    pub id: u32,
+    pub question: String,
+    /// Display name of who asked
+    pub asked_by: String,
+    pub answer: String,
+    /// Session offsets in seconds
+    pub start: f64,
+    pub end: f64,
+    pub answer_start: Option<f64>,
+    #[ts(type = "number[]")]
+    pub question_seqs: Vec<u64>,
+    #[ts(type = "number[]")]
+    pub answer_seqs: Vec<u64>,
+    /// No more chunks will join the segment
+    pub closed: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct QaSegmenter {
+    current: Option<QaSegment>,
+    /// Segments closed so far, kept for rebuilding
+    closed: Vec<QaSegment>,
+    next_id: u32,
+    /// The session ended, so no segment stays open
+    finished: bool,
+    /// Segment changes not yet sent as `qa-segment`
+    updates: Vec<QaSegment>,
+}
+
+impl QaSegmenter {
+    /// Place a speech chunk in a segment and set its `segment_id`
+    pub fn observe(&mut self, chunk: &mut TranscriptChunk) {
+        if chunk.kind != ChunkKind::Speech {
+            return;
+        }
+        // Without a role anyone could be asking, answers included
+        let asks = chunk.role == Some(SpeakerRole::Interviewer) && is_question(&chunk.text);
+        match self.current.as_mut() {
+            // More of the same question before anyone answered
+            Some(segment) if asks && segment.answer_seqs.is_empty() && segment.asked_by == chunk.speaker => {
+                join(&mut segment.question, &chunk.text);
+                segment.question_seqs.push(chunk.seq);
+                segment.end = chunk.end;
+            }
+            _ if asks => {
+                self.close_current();
+                self.next_id += 1;
+                self.current = Some(QaSegment {
+                    id: self.next_id,
+                    question: chunk.text.clone(),
+                    asked_by: chunk.speaker.clone(),
+                    answer: String::new(),
+                    start: chunk.start,
+                    end: chunk.end,
+                    answer_start: None,
+                    question_seqs: vec![chunk.seq],
+                    answer_seqs: Vec::new(),
+                    closed: false,
+                });
+            }
+            // Remarks and follow-ups of the asker stay in the segment without being answer
+            Some(segment) if segment.asked_by == chunk.speaker => segment.end = chunk.end,
+            Some(segment) => {
+                join(&mut segment.answer, &chunk.text);
+                segment.answer_start.get_or_insert(chunk.start);
+                segment.answer_seqs.push(chunk.seq);
+                segment.end = chunk.end;
+            }
+            None => return,
+        }
+        let segment = self.current.as_ref().expect("segment set above");
+        chunk.segment_id = Some(segment.id);
+        self.updates.push(segment.clone());
+    }
+
+    /// Close the open segment when the session ends
+    pub fn close(&mut self) {
+        self.finished = true;
+        self.close_current();
+    }
+
+    fn close_current(&mut self) {
+        if let Some(mut segment) = self.current.take() {
+            segment.closed = true;
+            self.updates.push(segment.clone());
+            self.closed.push(segment);
+        }
+    }
+
+    /// Segment `chunks` again after their speakers or roles changed, resetting
+    /// their `segment_id`. Only segments that differ from before are queued as
+    /// updates; segments no chunk refers to any more are gone and not sent.
+    pub fn rebuild(&mut self, chunks: &mut [TranscriptChunk]) {
+        let previous: Vec<QaSegment> = self.closed.drain(..).chain(self.current.take()).collect();
+        let pending = std::mem::take(&mut self.updates);
+        let finished = self.finished;
+        *self = Self::default();
+        for chunk in chunks.iter_mut() {
+            chunk.segment_id = None;
+            self.observe(chunk);
+        }
+        if finished {
+            self.close();
+        }
+        let rebuilt: Vec<QaSegment> = self.closed.iter().chain(&self.current).cloned().collect();
+        self.updates = pending;
+        self.updates.extend(rebuilt.into_iter().filter(|segment| !previous.contains(segment)));
+    }
+
+    pub fn take_updates(&mut self) -> Vec<QaSegment> {
+        std::mem::take(&mut self.updates)
+    }
+}
+
+fn join(text: &mut String, more: &str) {
+    if !text.is_empty() {
+        text.push(' ');
+    }
+    text.push_str(more);
+}
+
+/// Provider punctuation first, then an interrogative opener on any sentence
+fn is_question(text: &str) -> bool {
+    if text.contains('?') || text.contains('¿') {
+        return true;
+    }
+    text.split(['.', '!', ';'])
+        .map(|sentence| sentence.trim().trim_start_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .any(|sentence| {
+            INTERROGATIVE_OPENERS.iter().any(|opener| {
+                sentence.strip_prefix(opener).is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', ',', '\'']))
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn said(seq: u64, role: Option<SpeakerRole>, text: &str) -> TranscriptChunk {
+        let speaker = match role {
+            Some(SpeakerRole::Interviewer) => "Ana",
+            _ => "Ben",
+        };
+        let mut chunk = TranscriptChunk::speech(seq, speaker, text, seq as f64, seq as f64 + 0.9);
+        chunk.role = role;
+        chunk
+    }
+
+    #[test]
+    fn questions_by_punctuation_or_opener() {
+        assert!(is_question("Is that right?"));
+        assert!(is_question("¿Cuántos años tienes"));
+        assert!(is_question("Great. Tell me about your last project."));
+        assert!(is_question("walk me through it"));
+        assert!(is_question("How's the team set up"));
+        assert!(!is_question("Whatever works for you."));
+        assert!(!is_question("I explained it to them."));
+        assert!(!is_question("Sounds good"));
+    }
+
+    #[test]
+    fn question_opens_and_next_question_closes() {
+        let interviewer = Some(SpeakerRole::Interviewer);
+        let candidate = Some(SpeakerRole::Candidate);
+        let mut qa = QaSegmenter::default();
+        let mut chunks = vec![
+            said(1, interviewer, "Hi, thanks for joining."),
+            said(2, interviewer, "Tell me about yourself."),
+            said(3, interviewer, "Anything recent?"),
+            said(4, candidate, "Sure, I build data pipelines."),
+            said(5, interviewer, "Nice."),
+            said(6, candidate, "Mostly in Rust."),
+            said(7, interviewer, "Why Rust?"),
+        ];
+        for chunk in &mut chunks {
+            qa.observe(chunk);
+        }
+        let ids: Vec<Option<u32>> = chunks.iter().map(|c| c.segment_id).collect();
+        assert_eq!(ids, [None, Some(1), Some(1), Some(1), Some(1), Some(1), Some(2)]);
+
+        let updates = qa.take_updates();
+        let first = updates.iter().rev().find(|s| s.id == 1).unwrap();
+        assert!(first.closed);
+        assert_eq!(first.question, "Tell me about yourself. Anything recent?");
+        assert_eq!(first.answer, "Sure, I build data pipelines. Mostly in Rust.");
+        assert_eq!((first.question_seqs.clone(), first.answer_seqs.clone()), (vec![2, 3], vec![4, 6]));
+        assert_eq!(first.answer_start, Some(4.0));
+        assert!(!updates.last().unwrap().closed);
+    }
+
+    #[test]
+    fn unknown_roles_do_not_ask() {
+        let mut qa = QaSegmenter::default();
+        let mut chunk = said(1, None, "What do you mean?");
+        qa.observe(&mut chunk);
+        assert_eq!(chunk.segment_id, None);
+        assert!(qa.take_updates().is_empty());
+    }
+
+    #[test]
+    fn rebuild_refreshes_the_asker() {
+        let mut qa = QaSegmenter::default();
+        let mut chunks = vec![said(1, Some(SpeakerRole::Interviewer), "Why?")];
+        qa.observe(&mut chunks[0]);
+        qa.close();
+        qa.take_updates();
+
+        chunks[0].speaker = "Ana Pérez".to_string();
+        qa.rebuild(&mut chunks);
+        let updates = qa.take_updates();
+        assert_eq!(updates.len(), 1);
+        assert_eq!((updates[0].asked_by.as_str(), updates[0].closed), ("Ana Pérez", true));
+        qa.rebuild(&mut chunks);
+        assert!(qa.take_updates().is_empty());
+    }
+
+    #[test]
+    fn rebuild_segments_questions_asked_before_roles_were_known() {
+        let mut qa = QaSegmenter::default();
+        let mut chunks = vec![said(1, None, "Tell me about yourself."), said(2, None, "I build data pipelines.")];
+        chunks[0].speaker = "Ana".to_string();
+        for chunk in &mut chunks {
+            qa.observe(chunk);
+        }
+        assert!(qa.take_updates().is_empty());
+
+        chunks[0].role = Some(SpeakerRole::Interviewer);
+        chunks[1].role = Some(SpeakerRole::Candidate);
+        qa.rebuild(&mut chunks);
+        assert_eq!(chunks.iter().map(|c| c.segment_id).collect::<Vec<_>>(), [Some(1), Some(1)]);
+        let updates = qa.take_updates();
+        assert_eq!(updates.len(), 1);
+        assert_eq!((updates[0].answer.as_str(), updates[0].closed), ("I build data pipelines.", false));
+
+        // Later chunks keep joining the rebuilt segment
+        let mut next = said(3, Some(SpeakerRole::Candidate), "Mostly in Rust.");
+        qa.observe(&mut next);
+        assert_eq!(next.segment_id, Some(1));
+    }
+}
@@ -0,0 +1,642 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use ts_rs::TS;
+
+use crate::activity::{AudioSource, SourceMutes};
+use crate::analytics::ConversationAnalytics;
+use crate::audio;
+use crate::coaching::Coach;
+use crate::config::{self, CaptureConfig};
+use crate::credentials::{self, Credentials};
+use crate::diarization::Diarizer;
+use crate::error::{CaptureError, ErrorCode};
+use crate::events::{CredentialsNeeded, CredentialsReason};
+use crate::network::Network;
+use crate::speakers::SpeakerMap;
+use crate::stats::{CaptureStats, SessionStats};
+use crate::topics::TopicCoverage;
+use crate::transcript::{ChunkKind, TranscriptBackfill, TranscriptChunk, TranscriptLog};
+use crate::voiceprint;
+
+/// Max time stop_capture waits for the WebSocket task and capture threads
+const TEARDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Interval of the `capture-heartbeat` event
+const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Interval of the `conversation-analytics` event
+const ANALYTICS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum SessionState {
+    Starting,
+    Running,
+    Paused,
+    Stopping,
+    Stopped,
+    Failed,
+}
+
+impl SessionState {
+    /// Paused sessions keep their streams and provider connection open
+    pub fn is_active(self) -> bool {
+        matches!(self, SessionState::Starting | SessionState::Running | SessionState::Paused)
+    }
+
+    fn is_terminal(self) -> bool {
+        matches!(self, SessionState::Stopped | SessionState::Failed)
+    }
+
+    fn can_transition_to(self, next: SessionState) -> bool {
+        use SessionState::*;
+        matches!(
+            (self, next),
+            (Starting, Running)
+                | (Running, Paused)
+                | (Paused, Running)
+                | (Starting | Running | Paused, Stopping | Stopped | Failed)
+                | (Stopping, Stopped | Failed)
+        )
+    }
+}
+
+/// Returned by get_capture_status / stop_capture and sent on capture-state /
+/// capture-stopped / capture-heartbeat
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CaptureStatus {
+    /// True while starting, running or paused
+    pub is_recording: bool,
+    pub session_id: Option<String>,
+    #[ts(type = "number | null")]
+    pub interview_id: Option<i64>,
+    pub state: SessionState,
+    pub error: Option<CaptureError>,
+    /// Sources muted with set_source_muted
+    pub muted: Vec<SourceMute>,
+    /// None when no session has run yet
+    pub stats: Option<CaptureStats>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SourceMute {
+    pub source: AudioSource,
+    /// Session offset (seconds) at which the source was muted
+    pub since: f64,
+}
+
+// ============================================================================
+// Session — state shared by the manager, capture threads and WebSocket task
+// ============================================================================
+
+pub struct SessionShared {
+    pub id: String,
+    pub interview_id: i64,
+    pub transcript: Arc<Mutex<TranscriptLog>>,
+    pub stats: Arc<SessionStats>,
+    pub credentials: Credentials,
+    pub mutes: Arc<SourceMutes>,
+    /// Local speaker ids for segments the provider did not label
+    pub diarizer: Diarizer,
+    coach: Coach,
+    /// Configured STT provider, for checking language switches
+    provider: String,
+    /// STT stream language; the WebSocket task reconnects when it changes
+    language: watch::Sender<String>,
+    /// Capture threads keep their streams open while this is set
+    active: AtomicBool,
+    /// Audio is dropped instead of streamed while this is set
+    paused: AtomicBool,
+    /// Session offset (seconds) at which the current pause began
+    paused_at: Mutex<Option<f64>>,
+    /// Session offset (seconds) at which each muted source was muted
+    muted_at: Mutex<BTreeMap<AudioSource, f64>>,
+    /// Final chunks queued for analyze-chunk; dropped once the session ends
+    analysis_tx: Mutex<Option<mpsc::UnboundedSender<TranscriptChunk>>>,
+    state: Mutex<SessionState>,
+    error: Mutex<Option<CaptureError>>,
+}
+
+impl SessionShared {
+    fn new(
+        interview_id: i64,
+        transcript: Arc<Mutex<TranscriptLog>>,
+        config: &CaptureConfig,
+        diarizer: Diarizer,
+        analysis_tx: mpsc::UnboundedSender<TranscriptChunk>,
+    ) -> Self {
+        Self {
+            id: format!("{}-{}", interview_id, chrono::Utc::now().timestamp_millis()),
+            interview_id,
+            transcript,
+            stats: Arc::new(SessionStats::new(&config.provider, &config.model, &config.language)),
+            credentials: Credentials::new(&config.auth_token),
+            mutes: Arc::new(SourceMutes::default()),
+            diarizer,
+            coach: Coach::new(config.coaching_rules.clone()),
+            provider: config.provider.clone(),
+            language: watch::Sender::new(config.language.clone()),
+            active: AtomicBool::new(true),
+            paused: AtomicBool::new(false),
+            paused_at: Mutex::new(None),
+            muted_at: Mutex::new(BTreeMap::new()),
+            analysis_tx: Mutex::new(Some(analysis_tx)),
+            state: Mutex::new(SessionState::Starting),
+            error: Mutex::new(None),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    pub fn language(&self) -> String {
+        self.language.borrow().clone()
+    }
+
+    pub fn subscribe_language(&self) -> watch::Receiver<String> {
+        self.language.subscribe()
+    }
+
+    pub fn state(&self) -> SessionState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Move to `next` if allowed from the current state. Returns false (and
+    /// changes nothing) otherwise, e.g. when the session already ended.
+    pub fn transition(&self, app: &tauri::AppHandle, next: SessionState) -> bool {
+        {
+            let mut state = self.state.lock().unwrap();
+            if !state.can_transition_to(next) {
+                return false;
+            }
+            *state = next;
+            self.active.store(next.is_active(), Ordering::Release);
+            self.paused.store(next == SessionState::Paused, Ordering::Release);
+            if next.is_terminal() {
+                // Closing the queue lets the analysis task drain and exit
+                self.analysis_tx.lock().unwrap().take();
+            }
+        }
+        log::info!("Capture session {} → {:?}", self.id, next);
+        let _ = app.emit("capture-state", self.status());
+        true
+    }
+
+    pub fn fail(&self, app: &tauri::AppHandle, error: CaptureError) -> bool {
+        *self.error.lock().unwrap() = Some(error);
+        self.transition(app, SessionState::Failed)
+    }
+
+    /// Ask the HUD for a fresh auth token via `credentials-needed`
+    pub fn request_credentials(&self, app: &tauri::AppHandle, reason: CredentialsReason) {
+        log::info!("Capture session {} needs new credentials ({:?})", self.id, reason);
+        let _ = app.emit("credentials-needed", CredentialsNeeded {
+            session_id: self.id.clone(),
+            reason,
+            expires_at: self.credentials.expires_at(),
+        });
+    }
+
+    /// Record the current pause, if any, as a marker ending now
+    fn end_pause(&self, app: &tauri::AppHandle) {
+        let paused_at = self.paused_at.lock().unwrap().take();
+        if let Some(paused_at) = paused_at {
+            let marker = self.transcript.lock().unwrap().push_pause(paused_at);
+            self.publish(app, marker);
+        }
+    }
+
+    /// Mute or unmute one source. Unmuting records the span as a marker.
+    /// Returns false if the source already was in that state.
+    pub fn set_muted(&self, app: &tauri::AppHandle, source: AudioSource, muted: bool) -> bool {
+        if !self.mutes.set(source, muted) {
+            return false;
+        }
+        let mut muted_at = self.muted_at.lock().unwrap();
+        if muted {
+            muted_at.insert(source, self.transcript.lock().unwrap().elapsed());
+        } else if let Some(since) = muted_at.remove(&source) {
+            drop(muted_at);
+            let marker = self.transcript.lock().unwrap().push_mute(source, since);
+            self.publish(app, marker);
+        }
+        log::info!("Capture session {} {:?} {}", self.id, source, if muted { "muted" } else { "unmuted" });
+        true
+    }
+
+    /// Emit a final chunk to the HUD and queue it for analysis
+    pub fn publish(&self, app: &tauri::AppHandle, chunk: TranscriptChunk) {
+        if chunk.kind == ChunkKind::Speech {
+            self.stats.record_transcript();
+        }
+        let _ = app.emit("transcript", &chunk);
+        let (segments, topics) = {
+            let mut transcript = self.transcript.lock().unwrap();
+            (transcript.take_segment_updates(), transcript.take_topic_updates())
+        };
+        for segment in segments {
+            let _ = app.emit("qa-segment", segment);
+        }
+        for mention in topics {
+            let _ = app.emit("topic-covered", mention);
+        }
+        if let Some(tx) = self.analysis_tx.lock().unwrap().as_ref() {
+            if tx.send(chunk).is_ok() {
+                self.stats.analysis_queued();
+            }
+        }
+    }
+
+    /// Close the open question/answer segment and publish the final analytics
+    fn wrap_up(&self, app: &tauri::AppHandle) {
+        let segments = {
+            let mut transcript = self.transcript.lock().unwrap();
+            transcript.close_segment();
+            transcript.take_segment_updates()
+        };
+        for segment in segments {
+            let _ = app.emit("qa-segment", segment);
+        }
+        self.publish_analytics(app);
+    }
+
+    /// Recompute the conversation analytics, store them with the transcript
+    /// and emit `conversation-analytics`
+    pub fn publish_analytics(&self, app: &tauri::AppHandle) {
+        let snapshot = self.transcript.lock().unwrap().update_analytics();
+        let _ = app.emit("conversation-analytics", &snapshot);
+    }
+
+    /// Check the coaching rules and emit `coaching-nudge` if one fires
+    pub fn coach(&self, app: &tauri::AppHandle) {
+        if self.is_paused() {
+            return;
+        }
+        let nudge = {
+            let transcript = self.transcript.lock().unwrap();
+            self.coach.evaluate(transcript.chunks(), transcript.elapsed())
+        };
+        if let Some(nudge) = nudge {
+            let _ = app.emit("coaching-nudge", nudge);
+        }
+    }
+
+    pub fn status(&self) -> CaptureStatus {
+        let state = self.state();
+        CaptureStatus {
+            is_recording: state.is_active(),
+            session_id: Some(self.id.clone()),
+            interview_id: Some(self.interview_id),
+            state,
+            error: self.error.lock().unwrap().clone(),
+            muted: self
+                .muted_at
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(&source, &since)| SourceMute { source, since })
+                .collect(),
+            stats: Some(self.stats.snapshot()),
+        }
+    }
+}
+
+struct CaptureSession {
+    shared: Arc<SessionShared>,
+    stop_tx: mpsc::Sender<()>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl CaptureSession {
+    /// Signal the WebSocket task and capture threads, then wait for all of them
+    async fn teardown(&mut self) {
+        let _ = self.stop_tx.try_send(());
+        self.shared.active.store(false, Ordering::Release);
+        self.shared.analysis_tx.lock().unwrap().take();
+        let tasks = std::mem::take(&mut self.tasks);
+        if tokio::time::timeout(TEARDOWN_TIMEOUT, futures_util::future::join_all(tasks))
+            .await
+            .is_err()
+        {
+            log::warn!("Capture session {} teardown timed out", self.shared.id);
+        }
+    }
+}
+
+// ============================================================================
+// CaptureManager — registered as Tauri state, owns at most one session
+// ============================================================================
+
+#[derive(Default)]
+pub struct CaptureManager {
+    /// Held across start/stop so they never interleave
+    session: tokio::sync::Mutex<Option<CaptureSession>>,
+    /// Readable without waiting on an in-flight start/stop
+    current: Mutex<Option<Arc<SessionShared>>>,
+    /// Survives stop_capture so the HUD can still backfill after the session ends
+    transcript: Arc<Mutex<TranscriptLog>>,
+}
+
+impl CaptureManager {
+    pub async fn start(
+        &self,
+        app: tauri::AppHandle,
+        interview_id: i64,
+        config: CaptureConfig,
+    ) -> Result<String, CaptureError> {
+        // Reject bad input before touching the mic or the network
+        config.validate().map_err(|e| CaptureError::from(&e))?;
+        config.log_unknown_fields();
+        let network = Arc::new(Network::from_config(&config)?);
+
+        let mut slot = self.session.lock().await;
+        if let Some(prev) = slot.as_mut() {
+            if prev.shared.state().is_active() {
+                return Err(CaptureError::new(ErrorCode::AlreadyRecording, "Already recording")
+                    .with_context("sessionId", &prev.shared.id));
+            }
+            // Previous session ended on its own — make sure its threads are gone
+            prev.teardown().await;
+        }
+
+        let config = Arc::new(config);
+        self.transcript.lock().unwrap().reset(interview_id, &config.topics);
+        let (analysis_tx, analysis_rx) = mpsc::unbounded_channel::<TranscriptChunk>();
+        let shared = Arc::new(SessionShared::new(
+            interview_id,
+            self.transcript.clone(),
+            &config,
+            Diarizer::new(config.diarization, voiceprint::load(&app)),
+            analysis_tx,
+        ));
+        let _ = app.emit("capture-state", shared.status());
+
+        let (stop_tx, stop_rx) = mpsc::channel::<()>(1);
+
+        // Mixed audio channel — both mic and system audio send PCM16 mono 16kHz here
+        let (audio_tx, audio_rx) = mpsc::channel::<Vec<u8>>(200);
+        let mut tasks = audio::spawn_sources(audio_tx, &shared, &app);
+
+        let config_analysis = config.clone();
+        let shared_analysis = shared.clone();
+        let network_analysis = network.clone();
+        let app_analysis = app.clone();
+        tasks.push(tokio::spawn(async move {
+            audio::run_analysis(analysis_rx, &config_analysis, &network_analysis, &shared_analysis, &app_analysis)
+                .await;
+        }));
+
+        // Also watches the token expiry and checks the coaching rules. Not
+        // joined on teardown — it exits on the first tick after the session ends
+        let app_hb = app.clone();
+        let shared_hb = shared.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if !shared_hb.is_active() {
+                    break;
+                }
+                let _ = app_hb.emit("capture-heartbeat", shared_hb.status());
+                shared_hb.coach(&app_hb);
+                if shared_hb.credentials.should_request_refresh(chrono::Utc::now().timestamp()) {
+                    shared_hb.request_credentials(&app_hb, CredentialsReason::Expiring);
+                }
+            }
+        });
+
+        let app_analytics = app.clone();
+        let shared_analytics = shared.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(ANALYTICS_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if !shared_analytics.is_active() {
+                    break;
+                }
+                shared_analytics.publish_analytics(&app_analytics);
+            }
+        });
+
+        // === WebSocket + analysis on tokio ===
+        let app_ws = app.clone();
+        let shared_ws = shared.clone();
+        tasks.push(tokio::spawn(async move {
+            let result = audio::run_websocket(audio_rx, stop_rx, &config, &network, &shared_ws, &app_ws).await;
+            // After stop_capture moved us to Stopping it owns the rest of the teardown
+            if shared_ws.state() == SessionState::Stopping {
+                if let Err(e) = result {
+                    log::warn!("WebSocket closed with error during stop: {}", e);
+                }
+                return;
+            }
+            let ended_on_own = match result {
+                Ok(()) => shared_ws.transition(&app_ws, SessionState::Stopped),
+                Err(e) => {
+                    log::error!("WebSocket error: {}", e);
+                    let err = CaptureError::from(e);
+                    let _ = app_ws.emit("capture-error", &err);
+                    shared_ws.fail(&app_ws, err)
+                }
+            };
+            if ended_on_own {
+                shared_ws.wrap_up(&app_ws);
+                let _ = app_ws.emit("capture-stopped", shared_ws.status());
+            }
+        }));
+
+        *self.current.lock().unwrap() = Some(shared.clone());
+        *slot = Some(CaptureSession { shared, stop_tx, tasks });
+
+        Ok("Capture started (mic + system audio)".to_string())
+    }
+
+    /// Stop the session and wait for a full teardown before reporting its final state
+    pub async fn stop(&self, app: tauri::AppHandle) -> Result<CaptureStatus, CaptureError> {
+        let mut slot = self.session.lock().await;
+        let session = slot.as_mut().ok_or_else(not_recording)?;
+
+        let was_active = session.shared.transition(&app, SessionState::Stopping);
+        if !was_active && session.tasks.is_empty() {
+            return Err(not_recording());
+        }
+        // Close an open pause and open mute spans so the timeline shows them
+        session.shared.end_pause(&app);
+        for source in AudioSource::ALL {
+            session.shared.set_muted(&app, source, false);
+        }
+
+        session.teardown().await;
+        session.shared.transition(&app, SessionState::Stopped);
+
+        let status = session.shared.status();
+        if was_active {
+            session.shared.wrap_up(&app);
+            let _ = app.emit("capture-stopped", &status);
+        }
+        Ok(status)
+    }
+
+    /// Stop streaming audio while keeping the session and provider connection alive
+    pub fn pause(&self, app: &tauri::AppHandle) -> Result<CaptureStatus, CaptureError> {
+        let shared = self.current_session()?;
+        let offset = shared.transcript.lock().unwrap().elapsed();
+        if !shared.transition(app, SessionState::Paused) {
+            return Err(invalid_state("Cannot pause", shared.state()));
+        }
+        *shared.paused_at.lock().unwrap() = Some(offset);
+        Ok(shared.status())
+    }
+
+    /// Resume streaming and record the gap as a pause marker in the transcript
+    pub fn resume(&self, app: &tauri::AppHandle) -> Result<CaptureStatus, CaptureError> {
+        let shared = self.current_session()?;
+        if shared.state() != SessionState::Paused || !shared.transition(app, SessionState::Running) {
+            return Err(invalid_state("Cannot resume", shared.state()));
+        }
+        shared.end_pause(app);
+        Ok(shared.status())
+    }
+
+    /// Hand the running session a new STT auth token, used from its next connection on
+    pub fn update_credentials(&self, auth_token: &str) -> Result<CaptureStatus, CaptureError> {
+        let shared = self.current_session()?;
+        let auth_token = auth_token.trim();
+        if auth_token.is_empty() {
+            return Err(CaptureError::new(ErrorCode::InvalidConfig, "authToken is required")
+                .with_context("authToken", "is required"));
+        }
+        if credentials::token_expiry(auth_token).is_some_and(|exp| exp <= chrono::Utc::now().timestamp()) {
+            return Err(CaptureError::new(ErrorCode::InvalidConfig, "authToken is already expired")
+                .with_context("authToken", "is expired"));
+        }
+        shared.credentials.update(auth_token);
+        log::info!("Capture session {} credentials updated", shared.id);
+        Ok(shared.status())
+    }
+
+    /// Switch the STT stream language; the stream reconnects without stopping
+    /// the session, buffering audio meanwhile
+    pub fn set_language(&self, language: &str) -> Result<CaptureStatus, CaptureError> {
+        let shared = self.current_session()?;
+        let language = language.trim();
+        config::check_language(language, &shared.provider).map_err(|message| {
+            CaptureError::new(ErrorCode::InvalidConfig, format!("language {}", message))
+                .with_context("language", language)
+        })?;
+        if shared.language.send_if_modified(|current| {
+            let changed = current != language;
+            if changed {
+                *current = language.to_string();
+            }
+            changed
+        }) {
+            shared.stats.set_language(language);
+            log::info!("Capture session {} language → {}", shared.id, language);
+        }
+        Ok(shared.status())
+    }
+
+    /// Stop or resume sending one source's audio while the others keep flowing
+    pub fn set_source_muted(
+        &self,
+        app: &tauri::AppHandle,
+        source: AudioSource,
+        muted: bool,
+    ) -> Result<CaptureStatus, CaptureError> {
+        let shared = self.current_session()?;
+        if !shared.stats.has_source(source) {
+            return Err(CaptureError::new(
+                ErrorCode::InvalidState,
+                format!("{} is not being captured", source.label()),
+            )
+            .with_context("source", source.key()));
+        }
+        shared.set_muted(app, source, muted);
+        Ok(shared.status())
+    }
+
+    /// Name speakers for new chunks and relabel the local transcript, sending
+    /// `qa-segment` for segments that changed once it is segmented again with
+    /// the new roles. Works after stop too, so the transcript can be labeled
+    /// before export.
+    pub fn set_speaker_map(&self, app: &tauri::AppHandle, map: SpeakerMap) -> Result<TranscriptBackfill, CaptureError> {
+        let map = map.normalized()?;
+        let (backfill, segments) = {
+            let mut transcript = self.transcript.lock().unwrap();
+            transcript.set_speaker_map(map);
+            (transcript.since(0), transcript.take_segment_updates())
+        };
+        for segment in segments {
+            let _ = app.emit("qa-segment", segment);
+        }
+        Ok(backfill)
+    }
+
+    fn current_session(&self) -> Result<Arc<SessionShared>, CaptureError> {
+        self.current
+            .lock()
+            .unwrap()
+            .clone()
+            .filter(|s| s.state().is_active())
+            .ok_or_else(not_recording)
+    }
+
+    pub fn status(&self) -> CaptureStatus {
+        match self.current.lock().unwrap().as_ref() {
+            Some(shared) => shared.status(),
+            None => CaptureStatus {
+                is_recording: false,
+                session_id: None,
+                interview_id: None,
+                state: SessionState::Stopped,
+                error: None,
+                muted: Vec::new(),
+                stats: None,
+            },
+        }
+    }
+
+    pub fn transcript(&self, since_seq: u64) -> TranscriptBackfill {
+        self.transcript.lock().unwrap().since(since_seq)
+    }
+
+    /// Coverage of the `topics` of the current (or most recent) session
+    pub fn topic_coverage(&self) -> TopicCoverage {
+        self.transcript.lock().unwrap().topic_coverage()
+    }
+
+    /// Up-to-date analytics while recording, else those stored when the last session ended
+    pub fn analytics(&self) -> Option<ConversationAnalytics> {
+        match self.current_session() {
+            Ok(_) => Some(self.transcript.lock().unwrap().update_analytics()),
+            Err(_) => self.transcript.lock().unwrap().analytics(),
+        }
+    }
+}
+
+fn not_recording() -> CaptureError {
+    CaptureError::new(ErrorCode::NotRecording, "Not recording")
+}
+
+fn invalid_state(message: &str, state: SessionState) -> CaptureError {
+    CaptureError::new(ErrorCode::InvalidState, format!("{} a session that is {:?}", message, state))
+        .with_context("state", format!("{:?}", state).to_lowercase())
+}
@@ -0,0 +1,103 @@
+//! Names and roles the interviewer assigns to provider speaker ids
+//! (`speaker_0`, …) and on-device clusters (`local_0`, …), set with
+//! `set_speaker_map`. Mapping is by speaker id only; chunks carry no capture source.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use ts_rs::TS;
+
+use crate::error::{CaptureError, ErrorCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum SpeakerRole {
+    Interviewer,
+    Candidate,
+    Other,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SpeakerLabel {
+    #[serde(default)]
+    #[ts(optional)]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub role: Option<SpeakerRole>,
+}
+
+/// Argument of `set_speaker_map`
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SpeakerMap {
+    /// Keyed by speaker id: `speaker_0` or just `0` for provider labels,
+    /// `local_0` for on-device clusters
+    #[serde(default)]
+    #[ts(as = "Option<BTreeMap<String, SpeakerLabel>>", optional)]
+    pub speakers: BTreeMap<String, SpeakerLabel>,
+}
+
+impl SpeakerMap {
+    /// Normalize speaker ids to `speaker_N` or `local_N` and drop blank names
+    pub fn normalized(self) -> Result<Self, CaptureError> {
+        let mut speakers = BTreeMap::new();
+        for (key, label) in self.speakers {
+            let id = key.trim();
+            let (prefix, index) = match id.strip_prefix("local_") {
+                Some(index) => ("local", index),
+                None => ("speaker", id.strip_prefix("speaker_").unwrap_or(id)),
+            };
+            if index.parse::<u32>().is_err() {
+                return Err(CaptureError::new(
+                    ErrorCode::InvalidConfig,
+                    format!("Speaker id {:?} must be a number, speaker_<number> or local_<number>", key),
+                )
+                .with_context("speakers", key));
+            }
+            speakers.insert(format!("{}_{}", prefix, index), label.normalized());
+        }
+        Ok(Self { speakers })
+    }
+
+    /// Display name and role of a speech chunk
+    pub fn resolve(&self, speaker_id: &str) -> (String, Option<SpeakerRole>) {
+        let label = self.speakers.get(speaker_id);
+        let name = label.and_then(|l| l.name.clone()).unwrap_or_else(|| speaker_id.to_string());
+        (name, label.and_then(|l| l.role))
+    }
+}
+
+impl SpeakerLabel {
+    fn normalized(self) -> Self {
+        let name = self.name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        Self { name, ..self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(name: &str) -> SpeakerLabel {
+        SpeakerLabel { name: Some(name.to_string()), role: None }
+    }
+
+    #[test]
+    fn provider_and_local_ids_stay_apart() {
+        let map = SpeakerMap {
+            speakers: BTreeMap::from([("0".to_string(), named("Ana")), ("local_0".to_string(), named("Ben"))]),
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(map.resolve("speaker_0").0, "Ana");
+        assert_eq!(map.resolve("local_0").0, "Ben");
+        assert_eq!(map.resolve("local_1").0, "local_1");
+
+        let bad = SpeakerMap { speakers: BTreeMap::from([("local_x".to_string(), named("X"))]) };
+        assert!(bad.normalized().is_err());
+    }
+}
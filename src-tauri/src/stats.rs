@@ -0,0 +1,198 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use ts_rs::TS;
+
+use crate::activity::AudioSource;
+
+/// PCM16 mono at 16kHz
+const BYTES_PER_SECOND: f64 = 16000.0 * 2.0;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct AudioSourceInfo {
+    pub kind: AudioSource,
+    pub device: Option<String>,
+}
+
+/// Live counters of a capture session, part of `CaptureStatus`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct CaptureStats {
+    pub elapsed_secs: f64,
+    pub provider: String,
+    pub model: String,
+    /// Language the STT stream is set to (`auto` for detection)
+    pub language: String,
+    /// STT proxy endpoint in use
+    pub endpoint: Option<String>,
+    pub sources: Vec<AudioSourceInfo>,
+    #[ts(type = "number")]
+    pub bytes_streamed: u64,
+    pub seconds_streamed: f64,
+    /// Audio buffers dropped because the send queue was full
+    #[ts(type = "number")]
+    pub dropped_buffers: u64,
+    pub reconnects: u32,
+    pub provider_switches: u32,
+    pub endpoint_switches: u32,
+    /// Protocol version the proxy announced on `connected`
+    pub protocol_version: Option<u32>,
+    /// Proxy messages skipped because their type is newer than this build
+    #[ts(type = "number")]
+    pub unknown_messages: u64,
+    /// Proxy messages that were not valid JSON or did not match their type
+    #[ts(type = "number")]
+    pub malformed_messages: u64,
+    pub secs_since_last_transcript: Option<f64>,
+    /// Chunks queued or in flight to analyze-chunk
+    #[ts(type = "number")]
+    pub analysis_pending: u64,
+    #[ts(type = "number")]
+    pub analysis_failed: u64,
+}
+
+/// Counters updated from the capture callbacks, WebSocket and analysis tasks
+pub struct SessionStats {
+    started_at: Instant,
+    provider: Mutex<String>,
+    model: String,
+    language: Mutex<String>,
+    endpoint: Mutex<Option<String>>,
+    sources: Mutex<Vec<AudioSourceInfo>>,
+    bytes_streamed: AtomicU64,
+    dropped_buffers: AtomicU64,
+    reconnects: AtomicU32,
+    provider_switches: AtomicU32,
+    endpoint_switches: AtomicU32,
+    protocol_version: Mutex<Option<u32>>,
+    unknown_messages: AtomicU64,
+    malformed_messages: AtomicU64,
+    last_transcript_at: Mutex<Option<Instant>>,
+    analysis_pending: AtomicU64,
+    analysis_failed: AtomicU64,
+}
+
+impl SessionStats {
+    pub fn new(provider: &str, model: &str, language: &str) -> Self {
+        Self {
+            started_at: Instant::now(),
+            provider: Mutex::new(provider.to_string()),
+            model: model.to_string(),
+            language: Mutex::new(language.to_string()),
+            endpoint: Mutex::new(None),
+            sources: Mutex::new(Vec::new()),
+            bytes_streamed: AtomicU64::new(0),
+            dropped_buffers: AtomicU64::new(0),
+            reconnects: AtomicU32::new(0),
+            provider_switches: AtomicU32::new(0),
+            endpoint_switches: AtomicU32::new(0),
+            protocol_version: Mutex::new(None),
+            unknown_messages: AtomicU64::new(0),
+            malformed_messages: AtomicU64::new(0),
+            last_transcript_at: Mutex::new(None),
+            analysis_pending: AtomicU64::new(0),
+            analysis_failed: AtomicU64::new(0),
+        }
+    }
+
+    pub fn add_source(&self, kind: AudioSource, device: Option<String>) {
+        self.sources.lock().unwrap().push(AudioSourceInfo { kind, device });
+    }
+
+    pub fn has_source(&self, kind: AudioSource) -> bool {
+        self.sources.lock().unwrap().iter().any(|s| s.kind == kind)
+    }
+
+    pub fn set_provider(&self, provider: &str) {
+        *self.provider.lock().unwrap() = provider.to_string();
+    }
+
+    pub fn set_language(&self, language: &str) {
+        *self.language.lock().unwrap() = language.to_string();
+    }
+
+    pub fn set_endpoint(&self, endpoint: &str) {
+        *self.endpoint.lock().unwrap() = Some(endpoint.to_string());
+    }
+
+    pub fn record_endpoint_switch(&self, to: &str) {
+        self.endpoint_switches.fetch_add(1, Ordering::Relaxed);
+        self.set_endpoint(to);
+    }
+
+    pub fn record_streamed(&self, bytes: usize) {
+        self.bytes_streamed.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_dropped(&self) {
+        self.dropped_buffers.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_reconnect(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_provider_switch(&self, to: &str) {
+        self.provider_switches.fetch_add(1, Ordering::Relaxed);
+        self.set_provider(to);
+    }
+
+    pub fn set_protocol_version(&self, version: u32) {
+        *self.protocol_version.lock().unwrap() = Some(version);
+    }
+
+    pub fn record_unknown_message(&self) {
+        self.unknown_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_malformed_message(&self) {
+        self.malformed_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_transcript(&self) {
+        *self.last_transcript_at.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub fn analysis_queued(&self) {
+        self.analysis_pending.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn analysis_done(&self, ok: bool) {
+        self.analysis_pending.fetch_sub(1, Ordering::Relaxed);
+        if !ok {
+            self.analysis_failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> CaptureStats {
+        let bytes_streamed = self.bytes_streamed.load(Ordering::Relaxed);
+        CaptureStats {
+            elapsed_secs: self.started_at.elapsed().as_secs_f64(),
+            provider: self.provider.lock().unwrap().clone(),
+            model: self.model.clone(),
+            language: self.language.lock().unwrap().clone(),
+            endpoint: self.endpoint.lock().unwrap().clone(),
+            sources: self.sources.lock().unwrap().clone(),
+            bytes_streamed,
+            seconds_streamed: bytes_streamed as f64 / BYTES_PER_SECOND,
+            dropped_buffers: self.dropped_buffers.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            provider_switches: self.provider_switches.load(Ordering::Relaxed),
+            endpoint_switches: self.endpoint_switches.load(Ordering::Relaxed),
+            protocol_version: *self.protocol_version.lock().unwrap(),
+            unknown_messages: self.unknown_messages.load(Ordering::Relaxed),
+            malformed_messages: self.malformed_messages.load(Ordering::Relaxed),
+            secs_since_last_transcript: self
+                .last_transcript_at
+                .lock()
+                .unwrap()
+                .map(|t| t.elapsed().as_secs_f64()),
+            analysis_pending: self.analysis_pending.load(Ordering::Relaxed),
+            analysis_failed: self.analysis_failed.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Topic and skill coverage: the `topics` passed to `start_capture` are looked
+//! for in every final chunk by name and synonyms. The first mention emits
+//! `topic-covered`; `get_topic_coverage` sums them up.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::speakers::SpeakerRole;
+use crate::transcript::{ChunkKind, TranscriptChunk};
+
+/// One entry of `topics`, e.g. `{ "name": "Kubernetes", "synonyms": ["k8s", "kubectl"] }`
+#[derive(Debug, Clone, Deserialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Topic {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    #[ts(as = "Option<Vec<String>>", optional)]
+    pub synonyms: Vec<String>,
+}
+
+/// `topic-covered` — the first mention of a topic
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TopicMention {
+    pub topic: String,
+    /// Name or synonym as configured
+    pub term: String,
+    /// Display name of who raised it
+    pub speaker: String,
+    pub role: Option<SpeakerRole>,
+    #[ts(type = "number")]
+    pub seq: u64,
+    /// Session offset in seconds
+    pub at: f64,
+    pub timestamp: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TopicStatus {
+    pub topic: String,
+    pub first_mention: Option<TopicMention>,
+    pub mentions: u32,
+}
+
+/// Returned by `get_topic_coverage`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TopicCoverage {
+    /// In configured order
+    pub topics: Vec<TopicStatus>,
+    pub covered: u32,
+    pub total: u32,
+}
+
+impl Topic {
+    /// Problems with the topic, for `CaptureConfig::validate`
+    pub fn check(&self) -> Result<(), String> {
+        if normalize(&self.name).is_empty() {
+            return Err("name is required".to_string());
+        }
+        if self.synonyms.iter().any(|s| normalize(s).is_empty()) {
+            return Err(format!("{:?} has an empty synonym", self.name));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct TrackedTopic {
+    name: String,
+    /// (as configured, normalized and padded with spaces)
+    terms: Vec<(String, String)>,
+    /// Term and chunk seq of the first mention; who said it is looked up in
+    /// the transcript so speaker map changes apply
+    first_mention: Option<(String, u64)>,
+    mentions: u32,
+}
+
+impl TrackedTopic {
+    fn mention(&self, term: &str, chunk: &TranscriptChunk) -> TopicMention {
+        TopicMention {
+            topic: self.name.clone(),
+            term: term.to_string(),
+            speaker: chunk.speaker.clone(),
+            role: chunk.role,
+            seq: chunk.seq,
+            at: chunk.start,
+            timestamp: chunk.timestamp.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TopicTracker {
+    topics: Vec<TrackedTopic>,
+    /// First mentions not yet sent as `topic-covered`
+    updates: Vec<TopicMention>,
+}
+
+impl TopicTracker {
+    pub fn new(topics: &[Topic]) -> Self {
+        let topics = topics
+            .iter()
+            .map(|topic| TrackedTopic {
+                name: topic.name.trim().to_string(),
+                terms: std::iter::once(&topic.name)
+                    .chain(&topic.synonyms)
+                    .map(|term| (term.trim().to_string(), format!(" {} ", normalize(term))))
+                    .collect(),
+                first_mention: None,
+                mentions: 0,
+            })
+            .collect();
+        Self { topics, updates: Vec::new() }
+    }
+
+    /// Count the topics a speech chunk mentions
+    pub fn observe(&mut self, chunk: &TranscriptChunk) {
+        if chunk.kind != ChunkKind::Speech || self.topics.is_empty() {
+            return;
+        }
+        let text = format!(" {} ", normalize(&chunk.text));
+        for topic in &mut self.topics {
+            let Some((term, _)) = topic.terms.iter().find(|(_, needle)| text.contains(needle.as_str())) else {
+                continue;
+            };
+            topic.mentions += 1;
+            if topic.first_mention.is_none() {
+                let mention = topic.mention(term, chunk);
+                log::info!("Topic covered: {} (\"{}\", seq {})", mention.topic, mention.term, mention.seq);
+                topic.first_mention = Some((term.clone(), chunk.seq));
+                self.updates.push(mention);
+            }
+        }
+    }
+
+    pub fn take_updates(&mut self) -> Vec<TopicMention> {
+        std::mem::take(&mut self.updates)
+    }
+
+    /// Coverage with first mentions labeled as in `chunks` (the transcript, in seq order)
+    pub fn coverage(&self, chunks: &[TranscriptChunk]) -> TopicCoverage {
+        let topics: Vec<TopicStatus> = self
+            .topics
+            .iter()
+            .map(|t| TopicStatus {
+                topic: t.name.clone(),
+                first_mention: t.first_mention.as_ref().and_then(|(term, seq)| {
+                    chunks.get((*seq as usize).checked_sub(1)?).map(|chunk| t.mention(term, chunk))
+                }),
+                mentions: t.mentions,
+            })
+            .collect();
+        TopicCoverage {
+            covered: topics.iter().filter(|t| t.first_mention.is_some()).count() as u32,
+            total: topics.len() as u32,
+            topics,
+        }
+    }
+}
+
+/// Lowercase words separated by single spaces; `+` and `#` count as letters so
+/// `C++` and `C#` stay apart from `C`
+fn normalize(text: &str) -> String {
+    let mapped: String = text
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '+' || c == '#' { c } else { ' ' })
+        .collect();
+    mapped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic(name: &str, synonyms: &[&str]) -> Topic {
+        Topic { name: name.to_string(), synonyms: synonyms.iter().map(|s| s.to_string()).collect() }
+    }
+
+    fn tracked(topics: &[Topic], texts: &[&str]) -> (TopicTracker, Vec<TranscriptChunk>) {
+        let mut tracker = TopicTracker::new(topics);
+        let chunks: Vec<TranscriptChunk> = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| TranscriptChunk::speech(i as u64 + 1, "Ben", text, i as f64, i as f64 + 1.0))
+            .collect();
+        chunks.iter().for_each(|c| tracker.observe(c));
+        (tracker, chunks)
+    }
+
+    #[test]
+    fn normalize_keeps_plus_and_hash() {
+        assert_eq!(normalize("  Hello,   C++ and C#!  "), "hello c++ and c#");
+        assert_eq!(normalize("Node.js / k8s"), "node js k8s");
+        assert_eq!(normalize("¡Sí, Señor!"), "sí señor");
+        assert_eq!(normalize(" - "), "");
+    }
+
+    #[test]
+    fn terms_match_whole_words_only() {
+        let topics = [topic("C", &[]), topic("C++", &[]), topic("Go", &["golang"])];
+        let (tracker, chunks) = tracked(&topics, &["I mostly write C++ these days.", "Going forward, maybe."]);
+        let coverage = tracker.coverage(&chunks);
+        assert_eq!(coverage.topics[0].mentions, 0);
+        assert_eq!(coverage.topics[1].mentions, 1);
+        assert_eq!(coverage.topics[2].mentions, 0);
+        assert_eq!((coverage.covered, coverage.total), (1, 3));
+    }
+
+    #[test]
+    fn multi_word_synonyms_match_across_punctuation() {
+        let topics = [topic("Kubernetes", &["k8s", "container orchestration"])];
+        let (mut tracker, chunks) =
+            tracked(&topics, &["We did container-orchestration by hand.", "Then K8S.", "Kubernetes again"]);
+        let updates = tracker.take_updates();
+        assert_eq!(updates.len(), 1);
+        assert_eq!((updates[0].term.as_str(), updates[0].seq), ("container orchestration", 1));
+        assert_eq!(tracker.coverage(&chunks).topics[0].mentions, 3);
+    }
+
+    #[test]
+    fn coverage_follows_relabeled_speakers() {
+        let (tracker, mut chunks) = tracked(&[topic("Rust", &[])], &["I love Rust"]);
+        chunks[0].speaker = "Ben Ortiz".to_string();
+        chunks[0].role = Some(SpeakerRole::Candidate);
+        let first = tracker.coverage(&chunks).topics[0].first_mention.clone().unwrap();
+        assert_eq!((first.speaker.as_str(), first.role), ("Ben Ortiz", Some(SpeakerRole::Candidate)));
+    }
+
+    #[test]
+    fn topics_need_a_name_and_non_empty_synonyms() {
+        assert!(topic("Rust", &["rustlang"]).check().is_ok());
+        assert!(topic(" ?! ", &[]).check().is_err());
+        assert!(topic("Rust", &["", "x"]).check().is_err());
+    }
+}
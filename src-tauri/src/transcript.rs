@@ -0,0 +1,305 @@
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::activity::AudioSource;
+use crate::analytics::{self, ConversationAnalytics};
+use crate::diarization::RoleGuess;
+use crate::qa::{QaSegment, QaSegmenter};
+use crate::topics::{Topic, TopicCoverage, TopicMention, TopicTracker};
+use crate::speakers::{SpeakerMap, SpeakerRole};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum ChunkKind {
+    Speech,
+    /// Marker spanning a pause_capture → resume_capture gap
+    Pause,
+    /// Marker spanning a source muted with set_source_muted
+    Mute,
+}
+
+/// A final transcript segment as emitted on the `transcript` event and
+/// forwarded to analyze-chunk
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TranscriptChunk {
+    /// Monotonic per-session sequence number, starting at 1
+    #[ts(type = "number")]
+    pub seq: u64,
+    pub kind: ChunkKind,
+    /// Name from the speaker map, else `speaker_id`
+    pub speaker: String,
+    /// Provider label (`speaker_0`, …), on-device cluster (`local_0`, …),
+    /// `system` for markers
+    pub speaker_id: String,
+    /// Set when `speaker_id` comes from on-device diarization
+    pub speaker_confidence: Option<f64>,
+    /// From the speaker map, else from the interviewer voiceprint
+    pub role: Option<SpeakerRole>,
+    /// Set when `role` comes from the interviewer voiceprint
+    pub role_confidence: Option<f64>,
+    /// Voiceprint role, kept for when the speaker map changes
+    #[serde(skip)]
+    #[ts(skip)]
+    pub voice_role: Option<RoleGuess>,
+    /// Muted source, on mute markers
+    pub source: Option<AudioSource>,
+    pub text: String,
+    pub timestamp: String,
+    /// Seconds since the session started
+    pub start: f64,
+    pub end: f64,
+    /// `start` and `end` come from provider timestamps, not arrival time
+    #[serde(skip)]
+    #[ts(skip)]
+    pub provider_timed: bool,
+    pub confidence: f64,
+    pub provider: Option<String>,
+    /// Detected language with `language: "auto"`, else the session language
+    pub language: Option<String>,
+    /// Question/answer segment the chunk belongs to (see `qa-segment`)
+    pub segment_id: Option<u32>,
+}
+
+/// Response of `get_session_transcript` — everything after `since_seq`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TranscriptBackfill {
+    #[ts(type = "number | null")]
+    pub interview_id: Option<i64>,
+    #[ts(type = "number")]
+    pub last_seq: u64,
+    pub chunks: Vec<TranscriptChunk>,
+}
+
+/// One speaker's part of a final segment from the STT provider, before labels are added
+pub struct SpeechSegment {
+    pub speaker_id: String,
+    pub speaker_confidence: Option<f64>,
+    pub voice_role: Option<RoleGuess>,
+    pub text: String,
+    /// Session offsets in seconds
+    pub start: f64,
+    pub end: f64,
+    /// The offsets come from provider timestamps, not arrival time
+    pub provider_timed: bool,
+    pub confidence: f64,
+    pub provider: Option<String>,
+    pub language: Option<String>,
+}
+
+/// Transcript of the current (or most recent) capture session.
+/// Kept in memory so the HUD can recover chunks it missed across a page reload.
+#[derive(Debug, Default)]
+pub struct TranscriptLog {
+    interview_id: Option<i64>,
+    started_at: Option<std::time::Instant>,
+    chunks: Vec<TranscriptChunk>,
+    speaker_map: SpeakerMap,
+    /// Latest snapshot, kept with the transcript after the session ends
+    analytics: Option<ConversationAnalytics>,
+    qa: QaSegmenter,
+    topics: TopicTracker,
+}
+
+impl TranscriptLog {
+    /// Drop the previous session's transcript and start a new one
+    pub fn reset(&mut self, interview_id: i64, topics: &[Topic]) {
+        self.interview_id = Some(interview_id);
+        self.started_at = Some(std::time::Instant::now());
+        self.chunks.clear();
+        self.speaker_map = SpeakerMap::default();
+        self.analytics = None;
+        self.qa = QaSegmenter::default();
+        self.topics = TopicTracker::new(topics);
+    }
+
+    /// Append a final segment that just arrived
+    pub fn push(&mut self, segment: SpeechSegment) -> TranscriptChunk {
+        let (speaker, map_role) = self.speaker_map.resolve(&segment.speaker_id);
+        let (role, role_confidence) = pick_role(map_role, segment.voice_role);
+        self.append(TranscriptChunk {
+            seq: 0,
+            kind: ChunkKind::Speech,
+            speaker,
+            speaker_id: segment.speaker_id,
+            speaker_confidence: segment.speaker_confidence,
+            role,
+            role_confidence,
+            voice_role: segment.voice_role,
+            source: None,
+            text: segment.text,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            start: segment.start.max(0.0),
+            end: segment.end.max(0.0),
+            provider_timed: segment.provider_timed,
+            confidence: segment.confidence,
+            provider: segment.provider,
+            language: segment.language,
+            segment_id: None,
+        })
+    }
+
+    /// Use `map` for new chunks, relabel the ones already logged and segment
+    /// them into questions and answers again, as roles may have changed
+    pub fn set_speaker_map(&mut self, map: SpeakerMap) {
+        for chunk in self.chunks.iter_mut().filter(|c| c.kind == ChunkKind::Speech) {
+            let (speaker, map_role) = map.resolve(&chunk.speaker_id);
+            chunk.speaker = speaker;
+            (chunk.role, chunk.role_confidence) = pick_role(map_role, chunk.voice_role);
+        }
+        self.speaker_map = map;
+        self.qa.rebuild(&mut self.chunks);
+        if let Some(previous) = self.analytics.take() {
+            self.analytics = Some(analytics::analyze(&self.chunks, previous.elapsed_secs));
+        }
+    }
+
+    /// Recompute the conversation analytics up to now and keep them
+    pub fn update_analytics(&mut self) -> ConversationAnalytics {
+        let snapshot = analytics::analyze(&self.chunks, self.elapsed());
+        self.analytics = Some(snapshot.clone());
+        snapshot
+    }
+
+    pub fn analytics(&self) -> Option<ConversationAnalytics> {
+        self.analytics.clone()
+    }
+
+    /// Append a marker for a pause that started at offset `paused_at` and ends now
+    pub fn push_pause(&mut self, paused_at: f64) -> TranscriptChunk {
+        let end = self.elapsed();
+        let secs = (end - paused_at).max(0.0).round() as u64;
+        self.append(TranscriptChunk {
+            seq: 0,
+            kind: ChunkKind::Pause,
+            speaker: "system".to_string(),
+            speaker_id: "system".to_string(),
+            speaker_confidence: None,
+            role: None,
+            role_confidence: None,
+            voice_role: None,
+            source: None,
+            text: format!("[Interview paused for {}m {:02}s]", secs / 60, secs % 60),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            start: paused_at,
+            end,
+            provider_timed: false,
+            confidence: 1.0,
+            provider: None,
+            language: None,
+            segment_id: None,
+        })
+    }
+
+    /// Append a marker for `source` muted from offset `muted_at` until now
+    pub fn push_mute(&mut self, source: AudioSource, muted_at: f64) -> TranscriptChunk {
+        let end = self.elapsed();
+        let secs = (end - muted_at).max(0.0).round() as u64;
+        self.append(TranscriptChunk {
+            seq: 0,
+            kind: ChunkKind::Mute,
+            speaker: "system".to_string(),
+            speaker_id: "system".to_string(),
+            speaker_confidence: None,
+            role: None,
+            role_confidence: None,
+            voice_role: None,
+            source: Some(source),
+            text: format!("[{} muted for {}m {:02}s]", source.label(), secs / 60, secs % 60),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            start: muted_at,
+            end,
+            provider_timed: false,
+            confidence: 1.0,
+            provider: None,
+            language: None,
+            segment_id: None,
+        })
+    }
+
+    /// Close the open question/answer segment
+    pub fn close_segment(&mut self) {
+        self.qa.close();
+    }
+
+    /// Segment changes since the last call, for `qa-segment`
+    pub fn take_segment_updates(&mut self) -> Vec<QaSegment> {
+        self.qa.take_updates()
+    }
+
+    /// First topic mentions since the last call, for `topic-covered`
+    pub fn take_topic_updates(&mut self) -> Vec<TopicMention> {
+        self.topics.take_updates()
+    }
+
+    pub fn topic_coverage(&self) -> TopicCoverage {
+        self.topics.coverage(&self.chunks)
+    }
+
+    pub fn chunks(&self) -> &[TranscriptChunk] {
+        &self.chunks
+    }
+
+    /// Seconds since the session started
+    pub fn elapsed(&self) -> f64 {
+        self.started_at.map(|t| t.elapsed().as_secs_f64()).unwrap_or(0.0)
+    }
+
+    fn append(&mut self, mut chunk: TranscriptChunk) -> TranscriptChunk {
+        chunk.seq = self.chunks.len() as u64 + 1;
+        self.qa.observe(&mut chunk);
+        self.topics.observe(&chunk);
+        self.chunks.push(chunk.clone());
+        chunk
+    }
+
+    /// Chunks with `seq > since_seq` (seq is contiguous, so this is a slice)
+    pub fn since(&self, since_seq: u64) -> TranscriptBackfill {
+        let start = (since_seq as usize).min(self.chunks.len());
+        TranscriptBackfill {
+            interview_id: self.interview_id,
+            last_seq: self.chunks.len() as u64,
+            chunks: self.chunks[start..].to_vec(),
+        }
+    }
+}
+
+/// A role the interviewer set in the speaker map wins over the voiceprint's guess
+fn pick_role(map_role: Option<SpeakerRole>, voice_role: Option<RoleGuess>) -> (Option<SpeakerRole>, Option<f64>) {
+    match (map_role, voice_role) {
+        (Some(role), _) => (Some(role), None),
+        (None, Some(guess)) => (Some(guess.role), Some(guess.confidence)),
+        (None, None) => (None, None),
+    }
+}
+
+#[cfg(test)]
+impl TranscriptChunk {
+    /// Final speech chunk for tests in other modules
+    pub(crate) fn speech(seq: u64, speaker: &str, text: &str, start: f64, end: f64) -> Self {
+        Self {
+            seq,
+            kind: ChunkKind::Speech,
+            speaker: speaker.to_string(),
+            speaker_id: speaker.to_string(),
+            speaker_confidence: None,
+            role: None,
+            role_confidence: None,
+            voice_role: None,
+            source: None,
+            text: text.to_string(),
+            timestamp: String::new(),
+            start,
+            end,
+            provider_timed: true,
+            confidence: 0.9,
+            provider: None,
+            language: None,
+            segment_id: None,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Interviewer voiceprint: recorded once with `enroll_interviewer_voice` and
+//! kept in the app data dir. Sessions compare each speaker's voice with it to
+//! tag transcript chunks as interviewer or candidate.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::Manager;
+use ts_rs::TS;
+
+use crate::audio;
+use crate::diarization::{self, RoleGuess};
+use crate::error::{CaptureError, ErrorCode};
+use crate::speakers::SpeakerRole;
+
+const FILE_NAME: &str = "interviewer-voiceprint.json";
+
+/// Bumped when the embedding changes; older voiceprints are ignored
+const VERSION: u32 = 1;
+
+pub const DEFAULT_ENROLLMENT_SECS: u32 = 15;
+const ENROLLMENT_SECS: std::ops::RangeInclusive<u32> = 5..=60;
+
+/// Speech an enrollment sample must hold
+const MIN_ENROLLMENT_VOICED_SECS: f64 = 4.0;
+
+/// Voices closer than this to the voiceprint are the interviewer's. Looser
+/// than diarization's: enrollment is read out, interviews are spoken.
+const INTERVIEWER_DISTANCE: f64 = 0.2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Voiceprint {
+    version: u32,
+    embedding: Vec<f64>,
+    created_at: String,
+}
+
+/// Result of `enroll_interviewer_voice`
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct VoiceEnrollment {
+    pub recorded_secs: u32,
+    pub created_at: String,
+}
+
+impl Voiceprint {
+    /// Why the stored embedding cannot be compared with live voices, if it cannot
+    fn defect(&self) -> Option<String> {
+        if self.embedding.len() != diarization::EMBEDDING_LEN {
+            return Some(format!("{} values instead of {}", self.embedding.len(), diarization::EMBEDDING_LEN));
+        }
+        if self.embedding.iter().any(|v| !v.is_finite()) {
+            return Some("non-finite values".to_string());
+        }
+        None
+    }
+
+    /// Interviewer if `voice` is close to the voiceprint, candidate otherwise
+    pub fn role_of(&self, voice: &[f64]) -> RoleGuess {
+        let distance = diarization::voice_distance(&self.embedding, voice);
+        let role = if distance <= INTERVIEWER_DISTANCE { SpeakerRole::Interviewer } else { SpeakerRole::Candidate };
+        RoleGuess { role, confidence: diarization::match_confidence(distance, INTERVIEWER_DISTANCE) }
+    }
+}
+
+/// Record `duration_secs` of the default mic and store it as the interviewer's
+/// voiceprint, replacing any previous one
+pub async fn enroll(app: &tauri::AppHandle, duration_secs: Option<u32>) -> Result<VoiceEnrollment, anyhow::Error> {
+    let secs = duration_secs.unwrap_or(DEFAULT_ENROLLMENT_SECS);
+    if !ENROLLMENT_SECS.contains(&secs) {
+        return Err(CaptureError::new(
+            ErrorCode::InvalidConfig,
+            format!("Enrollment must last {} to {} seconds", ENROLLMENT_SECS.start(), ENROLLMENT_SECS.end()),
+        )
+        .with_context("durationSecs", secs.to_string())
+        .into());
+    }
+
+    log::info!("Recording {}s interviewer voice sample", secs);
+    let samples =
+        tokio::task::spawn_blocking(move || audio::record_mic(Duration::from_secs(u64::from(secs)))).await??;
+    let embedding = diarization::embed(&samples, MIN_ENROLLMENT_VOICED_SECS).ok_or_else(|| {
+        CaptureError::new(
+            ErrorCode::NotEnoughSpeech,
+            format!("Need at least {} seconds of speech; speak throughout the recording", MIN_ENROLLMENT_VOICED_SECS),
+        )
+    })?;
+
+    let voiceprint = Voiceprint { version: VERSION, embedding, created_at: chrono::Utc::now().to_rfc3339() };
+    let path = path(app)?;
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+    tokio::fs::write(&path, serde_json::to_vec(&voiceprint)?).await?;
+    log::info!("Interviewer voiceprint saved to {}", path.display());
+    Ok(VoiceEnrollment { recorded_secs: secs, created_at: voiceprint.created_at })
+}
+
+/// Delete the stored voiceprint. Returns whether there was one.
+pub async fn clear(app: &tauri::AppHandle) -> Result<bool, anyhow::Error> {
+    match tokio::fs::remove_file(path(app)?).await {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Stored voiceprint, if any. A missing, unreadable or malformed one only
+/// disables role tagging.
+pub fn load(app: &tauri::AppHandle) -> Option<Voiceprint> {
+    let path = path(app).ok()?;
+    let bytes = std::fs::read(&path).ok()?;
+    match serde_json::from_slice::<Voiceprint>(&bytes) {
+        Ok(v) if v.version != VERSION => {
+            log::warn!("Ignoring outdated interviewer voiceprint; enroll again");
+            None
+        }
+        Ok(v) => match v.defect() {
+            Some(defect) => {
+                log::warn!("Ignoring malformed interviewer voiceprint {} ({}); enroll again", path.display(), defect);
+                None
+            }
+            None => Some(v),
+        },
+        Err(e) => {
+            log::warn!("Ignoring unreadable interviewer voiceprint {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn path(app: &tauri::AppHandle) -> Result<PathBuf, anyhow::Error> {
+    Ok(app.path().app_data_dir()?.join(FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voiceprint(embedding: Vec<f64>) -> Voiceprint {
+        Voiceprint { version: VERSION, embedding, created_at: String::new() }
+    }
+
+    #[test]
+    fn only_well_formed_embeddings_are_used() {
+        assert!(voiceprint(vec![1.0; diarization::EMBEDDING_LEN]).defect().is_none());
+        assert!(voiceprint(vec![1.0; diarization::EMBEDDING_LEN - 1]).defect().is_some());
+        assert!(voiceprint(Vec::new()).defect().is_some());
+        let mut embedding = vec![1.0; diarization::EMBEDDING_LEN];
+        embedding[3] = f64::NAN;
+        assert!(voiceprint(embedding).defect().is_some());
+    }
+
+    #[test]
+    fn own_voice_is_the_interviewer() {
+        let mut stored = vec![0.0; diarization::EMBEDDING_LEN];
+        stored[diarization::EMBEDDING_LEN / 2..].fill(1.0);
+        let guess = voiceprint(stored.clone()).role_of(&stored);
+        assert_eq!(guess.role, SpeakerRole::Interviewer);
+        assert_eq!(guess.confidence, 1.0);
+
+        let mut other = stored.clone();
+        other[..diarization::EMBEDDING_LEN / 2].fill(2.0);
+        assert_eq!(voiceprint(stored).role_of(&other).role, SpeakerRole::Candidate);
+    }
+}
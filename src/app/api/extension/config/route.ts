@@ -4,6 +4,12 @@ import { corsHeaders, handleCors } from '@/lib/cors';
 
 export const dynamic = 'force-dynamic';
 
+// Ordered STT proxy endpoints (comma-separated); the desktop app fails over between them
+const sttEndpoints = (process.env.NEXT_PUBLIC_STT_ENDPOINTS || '')
+  .split(',')
+  .map((url) => url.trim())
+  .filter(Boolean);
+
 export async function OPTIONS() {
   return handleCors();
 }
@@ -32,7 +38,9 @@ export async function GET() {
         supabaseUrl: process.env.NEXT_PUBLIC_SUPABASE_URL,
         supabaseAnonKey: process.env.NEXT_PUBLIC_SUPABASE_ANON_KEY,
         dashboardUrl: process.env.NEXT_PUBLIC_APP_URL || 'https://interview-companion.triqual.dev',
-        sttProxyUrl: 'https://interview-stt-proxy.agusmontoya.workers.dev',
+        sttEndpoints,
+        // Single-endpoint clients
+        sttProxyUrl: sttEndpoints[0] ?? null,
       },
     }, { headers: corsHeaders });
   } catch (error) {
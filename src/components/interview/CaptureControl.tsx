@@ -9,6 +9,7 @@ interface CaptureControlProps {
   interviewId: number | null;
   config: {
     authToken: string;
+    sttEndpoints?: string[];
     sttProxyUrl?: string;
     provider?: string;
     model?: string;
@@ -45,9 +46,9 @@ export default function CaptureControl({
     // Listen for errors
     let cleanup: (() => void) | undefined;
     onCaptureError((err) => {
-      setError(err.error || err.message || 'Unknown capture error');
+      setError(err.message || 'Unknown capture error');
       setIsRecording(false);
-      onError?.(err.error || err.message || 'Unknown capture error');
+      onError?.(err.message || 'Unknown capture error');
     }).then((fn) => { cleanup = fn; });
 
     return () => cleanup?.();
@@ -4,51 +4,97 @@
  * Falls back gracefully when not running in Tauri (e.g., web browser)
  */
 
-export function isTauri(): boolean {
-  return typeof window !== 'undefined' && '__TAURI__' in window;
-}
+import type { AudioDevice } from './bindings/AudioDevice';
+import type { AudioSource } from './bindings/AudioSource';
+import type { CaptureConfig } from './bindings/CaptureConfig';
+import type { AudioSourceInfo } from './bindings/AudioSourceInfo';
+import type { CaptureError } from './bindings/CaptureError';
+import type { CaptureStats } from './bindings/CaptureStats';
+import type { CaptureStatus } from './bindings/CaptureStatus';
+import type { CoachingMetric } from './bindings/CoachingMetric';
+import type { CoachingNudge } from './bindings/CoachingNudge';
+import type { CoachingRule } from './bindings/CoachingRule';
+import type { ConversationAnalytics } from './bindings/ConversationAnalytics';
+import type { CredentialsNeeded } from './bindings/CredentialsNeeded';
+import type { CredentialsReason } from './bindings/CredentialsReason';
+import type { DiarizationMode } from './bindings/DiarizationMode';
+import type { ErrorCode } from './bindings/ErrorCode';
+import type { ExportFormat } from './bindings/ExportFormat';
+import type { ProviderSwitch } from './bindings/ProviderSwitch';
+import type { QaSegment } from './bindings/QaSegment';
+import type { SourceMute } from './bindings/SourceMute';
+import type { SpeakerAnalytics } from './bindings/SpeakerAnalytics';
+import type { SpeakerLabel } from './bindings/SpeakerLabel';
+import type { SpeakerMap } from './bindings/SpeakerMap';
+import type { SpeakerRole } from './bindings/SpeakerRole';
+import type { SttConnecting } from './bindings/SttConnecting';
+import type { SttOptionValue } from './bindings/SttOptionValue';
+import type { SttStalled } from './bindings/SttStalled';
+import type { Topic } from './bindings/Topic';
+import type { TopicCoverage } from './bindings/TopicCoverage';
+import type { TopicMention } from './bindings/TopicMention';
+import type { TopicStatus } from './bindings/TopicStatus';
+import type { TranscriptBackfill } from './bindings/TranscriptBackfill';
+import type { TranscriptChunk } from './bindings/TranscriptChunk';
+import type { VoiceEnrollment } from './bindings/VoiceEnrollment';
 
-interface AudioDevice {
-  name: string;
-  sampleRate: number | null;
-  channels: number | null;
-}
-
-interface CaptureConfig {
-  /** Clerk JWT for authenticating with the STT proxy */
-  authToken: string;
-  /** STT proxy URL (Cloudflare Worker) */
-  sttProxyUrl?: string;
-  /** STT provider: deepgram | assemblyai | revai */
-  provider?: string;
-  /** STT model (e.g., nova-3 for Deepgram) */
-  model?: string;
-  /** Transcription language */
-  language: string;
-  /** Supabase project URL */
-  supabaseUrl: string;
-  /** Supabase publishable key */
-  supabaseAnonKey: string;
-  /** Internal API key for Edge Functions */
-  internalApiKey: string;
-}
+// Command and event types are generated from the Rust crate (src-tauri) by ts-rs —
+// run `pnpm tauri:bindings` after changing them, never edit ./bindings by hand.
+export type {
+  AudioDevice,
+  AudioSource,
+  AudioSourceInfo,
+  CaptureConfig,
+  CaptureError,
+  CaptureStats,
+  CaptureStatus,
+  CoachingMetric,
+  CoachingNudge,
+  CoachingRule,
+  ConversationAnalytics,
+  CredentialsNeeded,
+  CredentialsReason,
+  DiarizationMode,
+  ErrorCode,
+  ExportFormat,
+  ProviderSwitch,
+  QaSegment,
+  SourceMute,
+  SpeakerAnalytics,
+  SpeakerLabel,
+  SpeakerMap,
+  SpeakerRole,
+  SttConnecting,
+  SttOptionValue,
+  SttStalled,
+  Topic,
+  TopicCoverage,
+  TopicMention,
+  TopicStatus,
+  TranscriptBackfill,
+  TranscriptChunk,
+  VoiceEnrollment,
+};
 
-interface CaptureStatus {
-  isRecording: boolean;
+/**
+ * Commands reject with a `CaptureError` — switch on `code`, show `message`
+ */
+export function isCaptureError(err: unknown): err is CaptureError {
+  return typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
 }
 
-interface TranscriptChunk {
-  speaker: string;
-  text: string;
-  timestamp: string;
-  confidence: number;
-  provider?: string;
+/**
+ * STT proxy endpoints from NEXT_PUBLIC_STT_ENDPOINTS (comma-separated, in order of preference)
+ */
+export function defaultSttEndpoints(): string[] {
+  return (process.env.NEXT_PUBLIC_STT_ENDPOINTS || '')
+    .split(',')
+    .map((url) => url.trim())
+    .filter(Boolean);
 }
 
-interface ProviderSwitch {
-  from: string;
-  to: string;
-  reason: string;
+export function isTauri(): boolean {
+  return typeof window !== 'undefined' && '__TAURI__' in window;
 }
 
 /**
@@ -62,6 +108,9 @@ export async function listAudioDevices(): Promise<AudioDevice[]> {
 
 /**
  * Start audio capture → STT Proxy → Provider → analyze-chunk
+ * Rejects up front with every invalid field listed if the config is incomplete.
+ * Rust health-checks the STT endpoints, uses the healthiest and fails over
+ * mid-session (reported on `provider-switch`).
  */
 export async function startCapture(
   interviewId: number,
@@ -73,35 +122,177 @@ export async function startCapture(
     interviewId,
     config: {
       authToken: config.authToken,
-      sttProxyUrl: config.sttProxyUrl || 'https://interview-stt-proxy.agusmontoya.workers.dev',
+      sttEndpoints: config.sttEndpoints?.length ? config.sttEndpoints : defaultSttEndpoints(),
+      sttProxyUrl: config.sttProxyUrl,
+      proxyUrl: config.proxyUrl,
+      caCertificates: config.caCertificates,
       provider: config.provider || 'deepgram',
       model: config.model || 'nova-3',
       language: config.language,
+      sttOptions: config.sttOptions,
+      diarization: config.diarization,
+      coachingRules: config.coachingRules,
+      topics: config.topics,
       supabaseUrl: config.supabaseUrl,
       supabaseAnonKey: config.supabaseAnonKey,
       internalApiKey: config.internalApiKey,
+      connectAttempts: config.connectAttempts,
+      connectTimeoutSecs: config.connectTimeoutSecs,
+      connectBackoffMs: config.connectBackoffMs,
+      keepaliveIntervalSecs: config.keepaliveIntervalSecs,
+      stallTimeoutSecs: config.stallTimeoutSecs,
     },
   });
 }
 
 /**
- * Stop audio capture
+ * Stop audio capture. Resolves once the session is fully torn down,
+ * with its final state (stopped or failed).
+ */
+export async function stopCapture(): Promise<CaptureStatus> {
+  if (!isTauri()) throw new Error('Not running in Tauri');
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<CaptureStatus>('stop_capture');
+}
+
+/**
+ * Pause capture — audio stops streaming but the session and provider connection stay open
+ */
+export async function pauseCapture(): Promise<CaptureStatus> {
+  if (!isTauri()) throw new Error('Not running in Tauri');
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<CaptureStatus>('pause_capture');
+}
+
+/**
+ * Resume a paused capture. A pause marker is added to the transcript and sent to analysis.
+ */
+export async function resumeCapture(): Promise<CaptureStatus> {
+  if (!isTauri()) throw new Error('Not running in Tauri');
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<CaptureStatus>('resume_capture');
+}
+
+/**
+ * Hand the running session a fresh Clerk JWT (answer to `credentials-needed`).
+ * Used from the next STT connection on; capture is not interrupted.
+ */
+export async function updateCaptureCredentials(authToken: string): Promise<CaptureStatus> {
+  if (!isTauri()) throw new Error('Not running in Tauri');
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<CaptureStatus>('update_capture_credentials', { authToken });
+}
+
+/**
+ * Switch the STT language of the running session (`'auto'` to detect it per
+ * segment, Deepgram only). The stream reconnects in the background once the
+ * utterance in progress is transcribed; no audio is lost.
+ */
+export async function setCaptureLanguage(language: string): Promise<CaptureStatus> {
+  if (!isTauri()) throw new Error('Not running in Tauri');
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<CaptureStatus>('set_capture_language', { language });
+}
+
+/**
+ * Mute or unmute one capture source while the others keep streaming.
+ * The span is added to the transcript as a `mute` marker on unmute.
+ */
+export async function setSourceMuted(source: AudioSource, muted: boolean): Promise<CaptureStatus> {
+  if (!isTauri()) throw new Error('Not running in Tauri');
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<CaptureStatus>('set_source_muted', { source, muted });
+}
+
+/**
+ * Name speakers by id (`speaker_0` from the provider, `local_0` from
+ * on-device diarization) and get the relabeled transcript back. New chunks
+ * and analyze-chunk payloads use the names from then on; also works after
+ * capture stopped, e.g. before export.
+ */
+export async function setSpeakerMap(map: SpeakerMap): Promise<TranscriptBackfill> {
+  if (!isTauri()) throw new Error('Not running in Tauri');
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<TranscriptBackfill>('set_speaker_map', { map });
+}
+
+/**
+ * Record the interviewer reading aloud for `durationSecs` (5–60, default 15)
+ * and store it as their voiceprint. Later sessions tag transcript chunks as
+ * interviewer or candidate with a `roleConfidence`. Rejects with
+ * NOT_ENOUGH_SPEECH if the recording held too little speech.
  */
-export async function stopCapture(): Promise<string> {
+export async function enrollInterviewerVoice(durationSecs?: number): Promise<VoiceEnrollment> {
   if (!isTauri()) throw new Error('Not running in Tauri');
   const { invoke } = await import('@tauri-apps/api/core');
-  return invoke<string>('stop_capture');
+  return invoke<VoiceEnrollment>('enroll_interviewer_voice', { durationSecs });
 }
 
 /**
- * Get capture status
+ * Delete the stored interviewer voiceprint. Resolves to whether there was one.
+ */
+export async function clearInterviewerVoice(): Promise<boolean> {
+  if (!isTauri()) return false;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<boolean>('clear_interviewer_voice');
+}
+
+/**
+ * Get capture status, with live session stats while a session exists
  */
 export async function getCaptureStatus(): Promise<CaptureStatus> {
-  if (!isTauri()) return { isRecording: false };
+  if (!isTauri()) {
+    return { isRecording: false, sessionId: null, interviewId: null, state: 'stopped', error: null, muted: [], stats: null };
+  }
   const { invoke } = await import('@tauri-apps/api/core');
   return invoke<CaptureStatus>('get_capture_status');
 }
 
+/**
+ * Fetch transcript chunks emitted after `sinceSeq` (all when omitted).
+ * Call on mount to backfill chunks missed during a reload or navigation.
+ */
+export async function getSessionTranscript(sinceSeq?: number): Promise<TranscriptBackfill> {
+  if (!isTauri()) return { interviewId: null, lastSeq: 0, chunks: [] };
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<TranscriptBackfill>('get_session_transcript', { sinceSeq });
+}
+
+/**
+ * Talk share, pace, monologues, interruptions, silences and answer latency per
+ * speaker — current while recording, else as stored when the last session
+ * ended. Null before any session.
+ */
+export async function getConversationAnalytics(): Promise<ConversationAnalytics | null> {
+  if (!isTauri()) return null;
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<ConversationAnalytics | null>('get_conversation_analytics');
+}
+
+/**
+ * Which of the `topics` passed to startCapture were mentioned, when first and
+ * by whom — for the current or most recent session.
+ */
+export async function getTopicCoverage(): Promise<TopicCoverage> {
+  if (!isTauri()) return { topics: [], covered: 0, total: 0 };
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<TopicCoverage>('get_topic_coverage');
+}
+
+/**
+ * Export the session transcript through a native save dialog, prefilled with
+ * `fileName` (a bare name, no directories) when given. Resolves with the
+ * written file path; rejects with EXPORT_CANCELLED if the dialog is dismissed.
+ */
+export async function exportTranscript(
+  format: ExportFormat,
+  fileName?: string
+): Promise<string> {
+  if (!isTauri()) throw new Error('Not running in Tauri');
+  const { invoke } = await import('@tauri-apps/api/core');
+  return invoke<string>('export_transcript', { format, fileName });
+}
+
 /**
  * Listen for transcript events from Rust
  */
@@ -130,16 +321,182 @@ export async function onProviderSwitch(
   return unlisten;
 }
 
+/**
+ * Listen for capture session state transitions
+ */
+export async function onCaptureState(
+  callback: (status: CaptureStatus) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen('capture-state', (event) => {
+    callback(event.payload as CaptureStatus);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for the periodic capture heartbeat (every 5s while a session is active)
+ */
+export async function onCaptureHeartbeat(
+  callback: (status: CaptureStatus) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<CaptureStatus>('capture-heartbeat', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for conversation analytics, every 15s while recording and once more
+ * when the session ends
+ */
+export async function onConversationAnalytics(
+  callback: (analytics: ConversationAnalytics) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<ConversationAnalytics>('conversation-analytics', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for coaching nudges from the `coachingRules` passed to startCapture.
+ * At most one every 30s, and each rule waits out its own cooldown.
+ */
+export async function onCoachingNudge(
+  callback: (nudge: CoachingNudge) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<CoachingNudge>('coaching-nudge', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for question/answer segments: sent when an interviewer question opens
+ * one, as the answer grows, and with `closed: true` once the next question
+ * (or the end of the session) closes it. Only speakers with the interviewer
+ * role (from the speaker map or the voiceprint) open segments. `setSpeakerMap`
+ * segments the transcript again and sends the segments that changed; ones no
+ * chunk refers to any more are gone. Chunks carry the id as `segmentId`.
+ */
+export async function onQaSegment(
+  callback: (segment: QaSegment) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<QaSegment>('qa-segment', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for the first mention of each of the `topics` passed to startCapture
+ */
+export async function onTopicCovered(
+  callback: (mention: TopicMention) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<TopicMention>('topic-covered', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for non-fatal capture problems (system audio fallback, live analysis failures)
+ */
+export async function onCaptureWarning(
+  callback: (warning: CaptureError) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<CaptureError>('capture-warning', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for requests for a fresh auth token — emitted a minute before the
+ * current one expires and when the STT proxy rejects it
+ */
+export async function onCredentialsNeeded(
+  callback: (data: CredentialsNeeded) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<CredentialsNeeded>('credentials-needed', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for the STT proxy rejecting the auth token (expired Clerk JWT).
+ * Capture keeps going while a new token is requested via `credentials-needed`;
+ * reported on `capture-error` with code STT_AUTH_EXPIRED only if none arrives in time.
+ */
+export async function onSttAuthExpired(
+  callback: (error: CaptureError) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<CaptureError>('stt-auth-expired', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for STT connection attempts, e.g. to show "connecting (attempt 2/5)".
+ * Audio captured meanwhile is buffered and sent once connected.
+ */
+export async function onSttConnecting(
+  callback: (data: SttConnecting) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<SttConnecting>('stt-connecting', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
+/**
+ * Listen for STT stalls — the proxy stopped answering while audio was flowing.
+ * Rust reconnects on its own; this is for surfacing a transient "reconnecting" state.
+ */
+export async function onSttStalled(
+  callback: (data: SttStalled) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<SttStalled>('stt-stalled', (event) => {
+    callback(event.payload);
+  });
+  return unlisten;
+}
+
 /**
  * Listen for capture error events
  */
 export async function onCaptureError(
-  callback: (error: { error?: string; message?: string; code?: string }) => void
+  callback: (error: CaptureError) => void
 ): Promise<() => void> {
   if (!isTauri()) return () => {};
   const { listen } = await import('@tauri-apps/api/event');
-  const unlisten = await listen('capture-error', (event) => {
-    callback(event.payload as any);
+  const unlisten = await listen<CaptureError>('capture-error', (event) => {
+    callback(event.payload);
   });
   return unlisten;
 }
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Entry of `list_audio_devices`
+ */
+export type AudioDevice = { name: string, sampleRate: number | null, channels: number | null, };
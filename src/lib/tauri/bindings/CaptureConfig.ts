@@ -0,0 +1,96 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CoachingRule } from "./CoachingRule";
+import type { DiarizationMode } from "./DiarizationMode";
+import type { SttOptionValue } from "./SttOptionValue";
+import type { Topic } from "./Topic";
+
+/**
+ * `start_capture` config. Required fields default to empty so that
+ * `validate()` can report every missing one at once instead of serde
+ * stopping at the first.
+ */
+export type CaptureConfig = { 
+/**
+ * Clerk JWT for authenticating with the STT proxy
+ */
+authToken: string, 
+/**
+ * STT proxy endpoints (Cloudflare Workers) in order of preference
+ */
+sttEndpoints?: Array<string>, 
+/**
+ * Single STT proxy URL, used when `sttEndpoints` is empty
+ */
+sttProxyUrl?: string, 
+/**
+ * STT provider: deepgram | assemblyai | revai
+ */
+provider?: string, 
+/**
+ * STT model (e.g., nova-3 for Deepgram)
+ */
+model?: string, 
+/**
+ * Transcription language (BCP-47, e.g. `es-419`), or `auto` to detect it
+ * per segment (Deepgram only); changeable mid-session with `set_capture_language`
+ */
+language?: string, 
+/**
+ * Extra provider options passed through to the STT proxy as query
+ * parameters, e.g. `{ "diarize": true, "utterance_end_ms": 1000 }`
+ */
+sttOptions?: { [key in string]?: SttOptionValue }, 
+/**
+ * Where speaker ids come from: `auto` (provider, on-device clustering
+ * when it gives none), `local` or `provider`
+ */
+diarization?: DiarizationMode, 
+/**
+ * Thresholds that emit `coaching-nudge`, in order of priority
+ */
+coachingRules?: Array<CoachingRule>, 
+/**
+ * Topics or skills to track mentions of, e.g. from the job position
+ */
+topics?: Array<Topic>, 
+/**
+ * Supabase project URL
+ */
+supabaseUrl: string, 
+/**
+ * Supabase publishable key
+ */
+supabaseAnonKey: string, 
+/**
+ * Internal API key for Edge Functions
+ */
+internalApiKey: string, 
+/**
+ * `http://`, `socks5://` or `socks5h://` proxy for all outbound traffic;
+ * `direct` ignores the environment. Unset: HTTPS_PROXY / ALL_PROXY / OS settings.
+ */
+proxyUrl?: string, 
+/**
+ * Extra trusted CA certificates (PEM contents or file paths) for TLS-inspecting proxies
+ */
+caCertificates?: Array<string>, 
+/**
+ * Connection attempts to the STT proxy before giving up
+ */
+connectAttempts?: number, 
+/**
+ * Timeout of a single connection attempt
+ */
+connectTimeoutSecs?: number, 
+/**
+ * Delay before the second attempt; doubles on each further one (with jitter)
+ */
+connectBackoffMs?: number, 
+/**
+ * KeepAlive is sent after this long without audio (muted, paused, VAD)
+ */
+keepaliveIntervalSecs?: number, 
+/**
+ * Reconnect when the proxy sends nothing for this long while audio is flowing
+ */
+stallTimeoutSecs?: number, };
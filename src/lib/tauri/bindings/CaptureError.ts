@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ErrorCode } from "./ErrorCode";
+
+export type CaptureError = { code: ErrorCode, message: string, retryable: boolean, 
+/**
+ * Details for the code, e.g. the invalid fields for INVALID_CONFIG or `status` for HTTP failures
+ */
+context: { [key in string]?: string }, };
@@ -0,0 +1,35 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AudioSourceInfo } from "./AudioSourceInfo";
+
+/**
+ * Live counters of a capture session, part of `CaptureStatus`
+ */
+export type CaptureStats = { elapsedSecs: number, provider: string, model: string, 
+/**
+ * Language the STT stream is set to (`auto` for detection)
+ */
+language: string, 
+/**
+ * STT proxy endpoint in use
+ */
+endpoint: string | null, sources: Array<AudioSourceInfo>, bytesStreamed: number, secondsStreamed: number, 
+/**
+ * Audio buffers dropped because the send queue was full
+ */
+droppedBuffers: number, reconnects: number, providerSwitches: number, endpointSwitches: number, 
+/**
+ * Protocol version the proxy announced on `connected`
+ */
+protocolVersion: number | null, 
+/**
+ * Proxy messages skipped because their type is newer than this build
+ */
+unknownMessages: number, 
+/**
+ * Proxy messages that were not valid JSON or did not match their type
+ */
+malformedMessages: number, secsSinceLastTranscript: number | null, 
+/**
+ * Chunks queued or in flight to analyze-chunk
+ */
+analysisPending: number, analysisFailed: number, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CaptureError } from "./CaptureError";
+import type { CaptureStats } from "./CaptureStats";
+import type { SessionState } from "./SessionState";
+import type { SourceMute } from "./SourceMute";
+
+/**
+ * Returned by get_capture_status / stop_capture and sent on capture-state /
+ * capture-stopped / capture-heartbeat
+ */
+export type CaptureStatus = { 
+/**
+ * True while starting, running or paused
+ */
+isRecording: boolean, sessionId: string | null, interviewId: number | null, state: SessionState, error: CaptureError | null, 
+/**
+ * Sources muted with set_source_muted
+ */
+muted: Array<SourceMute>, 
+/**
+ * None when no session has run yet
+ */
+stats: CaptureStats | null, };
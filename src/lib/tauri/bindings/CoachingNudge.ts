@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CoachingMetric } from "./CoachingMetric";
+import type { SpeakerRole } from "./SpeakerRole";
+
+/**
+ * `coaching-nudge`
+ */
+export type CoachingNudge = { ruleId: string, message: string, metric: CoachingMetric, role: SpeakerRole | null, value: number, 
+/**
+ * The `above` or `below` bound that was crossed
+ */
+threshold: number, 
+/**
+ * Session offset in seconds
+ */
+at: number, timestamp: string, };
@@ -0,0 +1,33 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CoachingMetric } from "./CoachingMetric";
+import type { SpeakerRole } from "./SpeakerRole";
+
+/**
+ * One entry of `coachingRules`, e.g. `{ "id": "talk-share", "metric": "talkShare",
+ * "role": "interviewer", "above": 0.4, "windowSecs": 300, "message": "Let the candidate talk" }`
+ */
+export type CoachingRule = { id: string, metric: CoachingMetric, 
+/**
+ * Whose speech the metric is about; everyone's when unset
+ */
+role?: SpeakerRole, 
+/**
+ * Fires when the metric is greater than this
+ */
+above?: number, 
+/**
+ * Fires when the metric is less than this
+ */
+below?: number, 
+/**
+ * Trailing window the metric is computed over; the whole session when unset
+ */
+windowSecs?: number, 
+/**
+ * Shown to the interviewer
+ */
+message: string, 
+/**
+ * Minimum time between two nudges of this rule
+ */
+cooldownSecs?: number, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SpeakerAnalytics } from "./SpeakerAnalytics";
+
+/**
+ * `conversation-analytics`, also returned by `get_conversation_analytics`
+ */
+export type ConversationAnalytics = { 
+/**
+ * Session offset the metrics run up to
+ */
+elapsedSecs: number, speechSecs: number, 
+/**
+ * Ordered by talk time, most first
+ */
+speakers: Array<SpeakerAnalytics>, 
+/**
+ * Speaker changes where the next speaker started before the previous finished
+ */
+overlaps: number, 
+/**
+ * Gaps of at least 3 s without speech, pauses excluded
+ */
+silenceGaps: number, silenceSecs: number, longestSilenceSecs: number, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CredentialsReason } from "./CredentialsReason";
+
+/**
+ * `credentials-needed` — answer with `update_capture_credentials`
+ */
+export type CredentialsNeeded = { sessionId: string, reason: CredentialsReason, 
+/**
+ * `exp` of the current token (unix seconds), if it has one
+ */
+expiresAt: number | null, };
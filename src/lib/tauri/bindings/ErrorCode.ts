@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ErrorCode = "INVALID_CONFIG" | "ALREADY_RECORDING" | "NOT_RECORDING" | "INVALID_STATE" | "MIC_PERMISSION_DENIED" | "DEVICE_NOT_FOUND" | "UNSUPPORTED_AUDIO_FORMAT" | "AUDIO_DEVICE_ERROR" | "SYSTEM_AUDIO_UNAVAILABLE" | "STT_AUTH_EXPIRED" | "STT_UNREACHABLE" | "STT_REJECTED" | "STT_QUOTA_EXCEEDED" | "STT_PROVIDER_ERROR" | "STT_CONNECTION_LOST" | "STT_STALLED" | "ANALYSIS_REJECTED" | "ANALYSIS_UNREACHABLE" | "NOTHING_TO_EXPORT" | "EXPORT_CANCELLED" | "NOT_ENOUGH_SPEECH" | "IO" | "INTERNAL";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `provider-switch` — forwarded from the STT proxy's provider failover, and
+ * emitted by the app itself on STT endpoint failover (`from`/`to` are hosts then)
+ */
+export type ProviderSwitch = { from: string, to: string, reason: string, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `qa-segment` — sent when a question opens a segment, as the answer comes
+ * in, and once more when the segment closes
+ */
+export type QaSegment = { 
+/**
+ * 1-based per session; also `segmentId` on the transcript chunks
+ */
+id: number, question: string, 
+/**
+ * Display name of who asked
+ */
+askedBy: string, answer: string, 
+/**
+ * Session offsets in seconds
+ */
+start: number, end: number, answerStart: number | null, questionSeqs: number[], answerSeqs: number[], 
+/**
+ * No more chunks will join the segment
+ */
+closed: boolean, };
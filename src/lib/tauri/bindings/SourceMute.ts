@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AudioSource } from "./AudioSource";
+
+export type SourceMute = { source: AudioSource, 
+/**
+ * Session offset (seconds) at which the source was muted
+ */
+since: number, };
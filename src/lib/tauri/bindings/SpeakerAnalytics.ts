@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SpeakerRole } from "./SpeakerRole";
+
+export type SpeakerAnalytics = { 
+/**
+ * Display name, as on the transcript chunks
+ */
+speaker: string, role: SpeakerRole | null, talkSecs: number, 
+/**
+ * 0–1 of all speech
+ */
+talkShare: number, words: number, wordsPerMinute: number, turns: number, longestMonologueSecs: number, 
+/**
+ * Times this speaker cut in on someone else
+ */
+interruptions: number, 
+/**
+ * Times someone else cut in on this speaker
+ */
+interrupted: number, 
+/**
+ * Mean gap between the previous speaker finishing and this one starting
+ */
+meanAnswerLatencySecs: number | null, lastAnswerLatencySecs: number | null, };
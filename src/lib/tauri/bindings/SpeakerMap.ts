@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SpeakerLabel } from "./SpeakerLabel";
+
+/**
+ * Argument of `set_speaker_map`
+ */
+export type SpeakerMap = { 
+/**
+ * Keyed by speaker id: `speaker_0` or just `0` for provider labels,
+ * `local_0` for on-device clusters
+ */
+speakers?: { [key in string]?: SpeakerLabel }, };
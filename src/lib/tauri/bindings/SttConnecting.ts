@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CaptureError } from "./CaptureError";
+
+/**
+ * `stt-connecting` — one per connection attempt to the STT proxy
+ */
+export type SttConnecting = { sessionId: string, 
+/**
+ * 1-based
+ */
+attempt: number, maxAttempts: number, 
+/**
+ * Why the previous attempt failed
+ */
+lastError: CaptureError | null, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `stt-stalled` — the proxy went quiet while audio was flowing; a reconnect follows
+ */
+export type SttStalled = { sessionId: string, 
+/**
+ * Time since the last message from the proxy
+ */
+silentSecs: number, stallTimeoutSecs: number, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One entry of `topics`, e.g. `{ "name": "Kubernetes", "synonyms": ["k8s", "kubectl"] }`
+ */
+export type Topic = { name: string, synonyms?: Array<string>, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TopicStatus } from "./TopicStatus";
+
+/**
+ * Returned by `get_topic_coverage`
+ */
+export type TopicCoverage = { 
+/**
+ * In configured order
+ */
+topics: Array<TopicStatus>, covered: number, total: number, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SpeakerRole } from "./SpeakerRole";
+
+/**
+ * `topic-covered` — the first mention of a topic
+ */
+export type TopicMention = { topic: string, 
+/**
+ * Name or synonym as configured
+ */
+term: string, 
+/**
+ * Display name of who raised it
+ */
+speaker: string, role: SpeakerRole | null, seq: number, 
+/**
+ * Session offset in seconds
+ */
+at: number, timestamp: string, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TopicMention } from "./TopicMention";
+
+export type TopicStatus = { topic: string, firstMention: TopicMention | null, mentions: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TranscriptChunk } from "./TranscriptChunk";
+
+/**
+ * Response of `get_session_transcript` — everything after `since_seq`
+ */
+export type TranscriptBackfill = { interviewId: number | null, lastSeq: number, chunks: Array<TranscriptChunk>, };
@@ -0,0 +1,51 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AudioSource } from "./AudioSource";
+import type { ChunkKind } from "./ChunkKind";
+import type { SpeakerRole } from "./SpeakerRole";
+
+/**
+ * A final transcript segment as emitted on the `transcript` event and
+ * forwarded to analyze-chunk
+ */
+export type TranscriptChunk = { 
+/**
+ * Monotonic per-session sequence number, starting at 1
+ */
+seq: number, kind: ChunkKind, 
+/**
+ * Name from the speaker map, else `speaker_id`
+ */
+speaker: string, 
+/**
+ * Provider label (`speaker_0`, …), on-device cluster (`local_0`, …),
+ * `system` for markers
+ */
+speakerId: string, 
+/**
+ * Set when `speaker_id` comes from on-device diarization
+ */
+speakerConfidence: number | null, 
+/**
+ * From the speaker map, else from the interviewer voiceprint
+ */
+role: SpeakerRole | null, 
+/**
+ * Set when `role` comes from the interviewer voiceprint
+ */
+roleConfidence: number | null, 
+/**
+ * Muted source, on mute markers
+ */
+source: AudioSource | null, text: string, timestamp: string, 
+/**
+ * Seconds since the session started
+ */
+start: number, end: number, confidence: number, provider: string | null, 
+/**
+ * Detected language with `language: "auto"`, else the session language
+ */
+language: string | null, 
+/**
+ * Question/answer segment the chunk belongs to (see `qa-segment`)
+ */
+segmentId: number | null, };
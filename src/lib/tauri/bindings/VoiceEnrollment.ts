@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Result of `enroll_interviewer_voice`
+ */
+export type VoiceEnrollment = { recordedSecs: number, createdAt: string, };